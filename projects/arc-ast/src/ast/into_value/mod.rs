@@ -1,117 +1,280 @@
 use crate::{
-    ast::{ASTKind, AST},
-    value::{Dict, Integer, List},
-    Value,
+    ast::{ASTKind, ExtendStatement, TextRange, AST},
+    utils::BUILD_EMPTY_SCOPE,
+    value::{Definition, Dict, Integer, Layer, List, MergePolicy, Provenance, Query, Text},
+    ErrorCode, Result, RuntimeError, Value,
 };
-use crate::value::Text;
-use crate::utils::BUILD_EMPTY_SCOPE;
+use std::{convert::TryFrom, path::PathBuf, str::FromStr};
 
+impl TryFrom<AST> for Value {
+    type Error = RuntimeError;
 
-impl From<AST> for Value {
-    fn from(ast: AST) -> Self {
-        Value::from(ast.kind)
+    fn try_from(ast: AST) -> Result<Self> {
+        let mut builder = Scope::default();
+        builder.build(ast)
     }
 }
 
-impl From<ASTKind> for Value {
-    fn from(ast: ASTKind) -> Self {
-        let mut builder = Scope::default();
-        builder.build(ast)
+impl TryFrom<ASTKind> for Value {
+    type Error = RuntimeError;
+
+    fn try_from(ast: ASTKind) -> Result<Self> {
+        Value::try_from(AST::from(ast))
+    }
+}
+
+impl Value {
+    /// Build the value of `ast` and record where each value was defined, `file` is the file the ast was parsed from.
+    pub fn try_explained(ast: AST, file: Option<PathBuf>) -> Result<(Value, Provenance)> {
+        let mut builder = Scope { file, provenance: Some(Provenance::default()), ..Scope::default() };
+        let value = builder.build(ast)?;
+        Ok((value, builder.provenance.unwrap_or_default()))
     }
 }
 
 pub struct Scope {
     top: Value,
+    /// Namespaces bound by `#import`, only visible to cites
+    imports: Value,
     pin_path: Vec<Vec<Value>>,
+    /// Whether the pin at the same depth comes from a list scope
+    pin_list: Vec<bool>,
     key_path: Vec<Vec<Value>>,
+    /// Set by `#merge`, used by `#extend`
+    policy: MergePolicy,
+    file: Option<PathBuf>,
+    /// Only recorded if present
+    provenance: Option<Provenance>,
+    /// The file of each import namespace
+    import_files: Vec<(Vec<Value>, PathBuf)>,
 }
 
 impl<'a> Default for Scope {
     fn default() -> Self {
-        Self { top: Value::from(Dict::default()), pin_path: vec![], key_path: vec![] }
+        Self { top: Value::from(Dict::default()), imports: Value::from(Dict::default()), pin_path: vec![], pin_list: vec![], key_path: vec![], policy: MergePolicy::default(), file: None, provenance: None, import_files: vec![] }
     }
 }
 
 impl Scope {
-    pub fn build(&mut self, ast: ASTKind) -> Value {
-        match ast {
-            ASTKind::Program(v) | ASTKind::Dict(v) => v.into_iter().for_each(|item| self.visit_ast(item.kind)),
-            ASTKind::String(v) => self.top = Value::from(*v),
-            ASTKind::Integer(v) => self.top = Value::from(*v),
-            _ => unimplemented!("ASTKind::{:?}", ast),
+    pub fn build(&mut self, ast: AST) -> Result<Value> {
+        match ast.kind {
+            ASTKind::Program(v) | ASTKind::Dict(v) => {
+                for item in v {
+                    self.visit_ast(item)?
+                }
+            }
+            _ => {
+                self.top = Value::Null;
+                self.visit_ast(ast)?
+            }
         }
-        self.top.to_owned()
+        Ok(self.top.to_owned())
+    }
+
+    pub fn visit_ast(&mut self, ast: AST) -> Result<()> {
+        let range = ast.range;
+        self.visit_kind(ast.kind, range).map_err(|e| e.with_range(range))
     }
 
-    pub fn visit_ast(&mut self, ast: ASTKind) {
+    fn visit_kind(&mut self, ast: ASTKind, range: Option<TextRange>) -> Result<()> {
         match ast {
-            ASTKind::ListScope(depth, path) | ASTKind::DictScope(depth, path) => {
-                // println!("{} vs {}", depth,self.pin_path.len());
-                match depth >= self.pin_path.len() {
-                    true => self.push_pin(path.kind),
-                    false => {
-                        self.pin_path.truncate(depth);
-                        self.push_pin(path.kind)
-                    }
+            ASTKind::None | ASTKind::Span(_) => (),
+            ASTKind::Program(v) | ASTKind::Sequence(v) => {
+                for item in v {
+                    self.visit_ast(item)?;
                 }
-                if BUILD_EMPTY_SCOPE {
-                    self.get_pointer();
+            }
+            ASTKind::ListScope(depth, path) => self.visit_scope(depth, *path, true)?,
+            ASTKind::DictScope(depth, path) => self.visit_scope(depth, *path, false)?,
+            ASTKind::ListInsert(v) => {
+                self.push_next_index()?;
+                for item in v {
+                    self.visit_ast(item)?;
                 }
+                self.pop_index();
+            }
+            ASTKind::ListAppend(v) => {
+                self.push_next_index()?;
+                self.visit_ast(*v)?;
+                self.pop_index();
             }
             ASTKind::List(v) => {
+                // an empty literal still sets the value
+                if let Value::Null = self.get_pointer()? {
+                    *self.get_pointer()? = List::empty()
+                }
+                self.define(range, Layer::Document)?;
                 for (index, item) in v.into_iter().enumerate() {
                     self.push_index(index);
-                    self.visit_ast(item.kind);
+                    self.visit_ast(item)?;
                     self.pop_index();
                 }
             }
             ASTKind::Dict(v) => {
+                if let Value::Null = self.get_pointer()? {
+                    *self.get_pointer()? = Dict::empty()
+                }
+                self.define(range, Layer::Document)?;
                 for item in v {
-                    self.visit_ast(item.kind);
+                    self.visit_ast(item)?;
                 }
             }
             ASTKind::Pair(key, value) => {
-                self.push_key(key.kind);
-                self.visit_ast(value.kind);
+                self.push_key(*key)?;
+                self.visit_ast(*value)?;
                 self.pop_key();
             }
+            ASTKind::ExtendStatement(v) => self.visit_extend(*v)?,
+            ASTKind::MergeStatement(v) => self.policy.apply_directive(&v.strategy, v.key.as_deref(), &v.path)?,
+            ASTKind::ImportStatement(v) => {
+                let v = *v;
+                let namespace = self.extract_namespace(v.namespace)?;
+                self.import_files.push((namespace.clone(), v.source.get_path().clone()));
+                *self.imports.ensure_path(&namespace)? = v.source.value;
+            }
             ASTKind::Null => {
-                self.get_pointer();
+                self.get_pointer()?;
+                self.define(range, Layer::Document)?;
             }
             ASTKind::Cite(v) => {
-                let cite = self.extract_namespace(v.kind);
-                *self.get_pointer() = self.top.get_value(&cite).clone();
-            }
-            ASTKind::Boolean(v) => *self.get_pointer() = Value::Boolean(v),
-            ASTKind::Integer(v) => *self.get_pointer() = Value::Integer(v),
-            ASTKind::Decimal(v) => *self.get_pointer() = Value::Decimal(v),
-            ASTKind::String(v) => *self.get_pointer() = Value::String(v),
-            _ => unimplemented!("ASTKind::{:?}", ast),
+                let cite = self.extract_namespace(*v)?;
+                let (value, layer) = match cite.first() {
+                    Some(Value::String(s)) if self.imports.has_key(s.as_str()) => (self.imports.get_value(&cite).clone(), self.import_layer(&cite)),
+                    _ => (self.top.get_value(&cite).clone(), Layer::Document),
+                };
+                *self.get_pointer()? = value;
+                self.define(range, layer)?;
+            }
+            ASTKind::CiteQuery(v) => {
+                let query = Query::from_str(&v)?;
+                let (source, layer) = match query.first_key() {
+                    Some(s) if self.imports.has_key(s) => (&self.imports, self.import_layer(&[Value::from(s)])),
+                    _ => (&self.top, Layer::Document),
+                };
+                let items: Vec<Value> = query.find(source).into_iter().map(|m| m.value.clone()).collect();
+                *self.get_pointer()? = Value::from(items);
+                self.define(range, layer)?;
+            }
+            ASTKind::Boolean(v) => self.set_value(Value::Boolean(v), range)?,
+            ASTKind::Integer(v) => self.set_value(Value::Integer(v), range)?,
+            ASTKind::Decimal(v) => self.set_value(Value::Decimal(v), range)?,
+            ASTKind::DateTime(v) => self.set_value(Value::DateTime(v), range)?,
+            ASTKind::String(v) => self.set_value(Value::String(v), range)?,
+            ASTKind::Handler(handler, v) => {
+                self.visit_ast(*v)?;
+                match self.get_pointer()? {
+                    Value::List(v) => v.set_handler(handler),
+                    Value::Dict(v) => v.set_handler(handler),
+                    _ => (),
+                }
+            }
+            ASTKind::Value(v) => self.set_value(*v, range)?,
+            ASTKind::Namespace(_) => {
+                return Err(RuntimeError::structure_error(ErrorCode::UnexpectedNode, "a namespace can not be used as value"));
+            }
         }
+        Ok(())
     }
 
-    fn get_pointer(&mut self) -> &mut Value {
-        let mut pointer = &mut self.top;
-        for path in self.pin_path.iter().flatten().chain(self.key_path.iter().flatten()) {
-            match path {
-                Value::String(key) => pointer = pointer.ensure_key(key.as_ref().to_owned()),
-                Value::Integer(index) => {
-                    pointer = pointer.ensure_index(index.as_ref().to_owned());
-                }
-                _ => unreachable!(),
-            }
+    fn set_value(&mut self, value: Value, range: Option<TextRange>) -> Result<()> {
+        *self.get_pointer()? = value;
+        self.define(range, Layer::Document)
+    }
+
+    /// Record a definition at the current pointer, which must exist.
+    fn define(&mut self, range: Option<TextRange>, layer: Layer) -> Result<()> {
+        if self.provenance.is_none() {
+            return Ok(());
         }
-        return pointer;
+        let path = self.concrete_path()?;
+        let definition = Definition { file: self.file.clone(), range, layer };
+        if let Some(provenance) = &mut self.provenance {
+            provenance.define(path, definition)
+        }
+        Ok(())
     }
 
-    fn push_pin(&mut self, namespace: ASTKind) {
-        let namespace = self.extract_namespace(namespace);
-        self.pin_path.push(namespace)
+    fn visit_extend(&mut self, extend: ExtendStatement) -> Result<()> {
+        let policy = self.policy.clone();
+        if self.provenance.is_none() {
+            self.get_pointer()?.merge_with(extend.value, &policy);
+            return Ok(());
+        }
+        let before = self.get_pointer()?.clone();
+        self.get_pointer()?.merge_with(extend.value.clone(), &policy);
+        let after = self.get_pointer()?.clone();
+        let path = self.concrete_path()?;
+        let file = extend.get_path().clone();
+        let definition = Definition { file: Some(file.clone()), range: None, layer: Layer::Extend(file) };
+        if let Some(provenance) = &mut self.provenance {
+            provenance.merge_layer(&path, &before, &after, &extend.value, &extend.provenance, &definition)
+        }
+        Ok(())
     }
 
-    fn push_key(&mut self, namespace: ASTKind) {
-        let namespace = self.extract_namespace(namespace);
-        self.key_path.push(namespace)
+    fn import_layer(&self, cite: &[Value]) -> Layer {
+        match self.import_files.iter().rev().find(|(namespace, _)| cite.starts_with(namespace)) {
+            Some((_, file)) => Layer::Import(file.clone()),
+            None => Layer::Document,
+        }
+    }
+
+    /// The current pointer as keys and indices, negative indices are resolved against the built value.
+    fn concrete_path(&self) -> Result<Vec<String>> {
+        let mut out = vec![];
+        let mut pointer = &self.top;
+        for item in self.pin_path.iter().flatten().chain(self.key_path.iter().flatten()) {
+            let token = match (item, pointer) {
+                (Value::String(key), _) => key.as_str().to_string(),
+                (Value::Integer(index), Value::List(list)) => match index.get_index().and_then(|i| list.resolve_index(i)) {
+                    Some(s) => s.to_string(),
+                    None => index.to_string(),
+                },
+                (Value::Integer(index), _) => index.to_string(),
+                _ => return Err(RuntimeError::structure_error(ErrorCode::InvalidKey, format!("{:?} is neither a key nor an index", item))),
+            };
+            pointer = pointer.get_value(std::slice::from_ref(item));
+            out.push(token);
+        }
+        Ok(out)
+    }
+
+    fn get_pointer(&mut self) -> Result<&mut Value> {
+        let path: Vec<Value> = self.pin_path.iter().flatten().chain(self.key_path.iter().flatten()).cloned().collect();
+        self.top.ensure_path(&path)
+    }
+
+    fn visit_scope(&mut self, depth: usize, namespace: AST, is_list: bool) -> Result<()> {
+        if depth < self.pin_path.len() {
+            self.pin_path.truncate(depth);
+            self.pin_list.truncate(depth);
+        }
+        let mut namespace = self.extract_namespace(namespace)?;
+        // child scope of a list scope refers to the last element
+        if let Some(true) = self.pin_list.last() {
+            namespace.insert(0, Value::from(-1));
+        }
+        self.pin_path.push(namespace);
+        self.pin_list.push(is_list);
+        if BUILD_EMPTY_SCOPE {
+            self.get_pointer()?;
+        }
+        Ok(())
+    }
+
+    fn push_next_index(&mut self) -> Result<()> {
+        let index = match self.get_pointer()? {
+            Value::List(list) => list.next_index(),
+            _ => 0,
+        };
+        self.push_index(index);
+        Ok(())
+    }
+
+    fn push_key(&mut self, namespace: AST) -> Result<()> {
+        let namespace = self.extract_namespace(namespace)?;
+        self.key_path.push(namespace);
+        Ok(())
     }
 
     fn pop_key(&mut self) -> Option<Vec<Value>> {
@@ -127,21 +290,27 @@ impl Scope {
         self.key_path.pop()
     }
 
-    fn extract_namespace(&self, namespace: ASTKind) -> Vec<Value> {
+    fn extract_namespace(&self, namespace: AST) -> Result<Vec<Value>> {
         let mut out = vec![];
-        match namespace {
+        match namespace.kind {
             ASTKind::Namespace(ns) => {
                 for item in ns {
                     match item.kind {
                         ASTKind::String(v) => out.push(Value::from(*v)),
                         ASTKind::Integer(v) => out.push(Value::from(*v)),
-                        _ => unreachable!(),
+                        _ => {
+                            let e = RuntimeError::structure_error(ErrorCode::InvalidKey, "expect a key or an index");
+                            return Err(e.with_range(item.range));
+                        }
                     }
                 }
             }
-            _ => unreachable!(),
+            _ => {
+                let e = RuntimeError::structure_error(ErrorCode::InvalidKey, "expect a namespace");
+                return Err(e.with_range(namespace.range));
+            }
         };
-        return out;
+        Ok(out)
     }
 }
 
@@ -168,30 +337,41 @@ impl Value {
                     }
                     return &Value::Null;
                 }
-                _ => unreachable!(),
+                _ => return &Value::Null,
             }
         }
         return out;
     }
 
-    pub fn ensure_key(&mut self, key: Text) -> &'_ mut Value {
+    pub fn ensure_path(&mut self, path: &[Value]) -> Result<&'_ mut Value> {
+        let mut pointer = self;
+        for item in path {
+            match item {
+                Value::String(key) => pointer = pointer.ensure_key(key.as_ref().to_owned())?,
+                Value::Integer(index) => pointer = pointer.ensure_index(index.as_ref().to_owned())?,
+                _ => return Err(RuntimeError::structure_error(ErrorCode::InvalidKey, format!("{:?} is neither a key nor an index", item))),
+            }
+        }
+        Ok(pointer)
+    }
+    pub fn ensure_key(&mut self, key: Text) -> Result<&'_ mut Value> {
         match self {
             Value::Null => {
                 *self = Dict::empty();
                 self.ensure_key(key)
             }
-            Value::Dict(dict) => dict.ensure_key(key),
-            _ => unimplemented!("{:?}", self),
+            Value::Dict(dict) => Ok(dict.ensure_key(key)),
+            _ => Err(RuntimeError::structure_error(ErrorCode::NotDict, format!("can not set key `{}` under {:?}", key.as_str(), self))),
         }
     }
-    pub fn ensure_index(&mut self, index: Integer) -> &'_ mut Value {
+    pub fn ensure_index(&mut self, index: Integer) -> Result<&'_ mut Value> {
         match self {
             Value::Null => {
                 *self = List::empty();
                 self.ensure_index(index)
             }
             Value::List(list) => list.ensure_index(index),
-            _ => unimplemented!("{:?}", self),
+            _ => Err(RuntimeError::structure_error(ErrorCode::NotList, format!("can not set index `{}` under {:?}", index, self))),
         }
     }
 }
@@ -5,12 +5,12 @@ mod statements;
 
 pub use crate::ast::range::TextRange;
 use crate::{
-    value::{parse_number, Decimal, Integer, Text, TextDelimiter},
+    value::{parse_number, DateTime, Decimal, Integer, Text, TextDelimiter},
     Value,
 };
 use num::{BigInt, Num};
 use std::fmt::{self, Debug, Formatter};
-pub use statements::{ExtendFormat,ExtendStatement};
+pub use statements::{ExtendFormat, ExtendStatement, ImportStatement, MergeStatement};
 
 #[derive(Clone, Eq, PartialEq)]
 pub struct AST {
@@ -34,8 +34,16 @@ pub enum ASTKind {
     /// Plain Text, NewLines
     Span(String),
     ExtendStatement(Box<ExtendStatement>),
+    /// `#import json "file.json" as namespace`
+    ImportStatement(Box<ImportStatement>),
+    /// `#merge union(id) "servers"`
+    MergeStatement(Box<MergeStatement>),
     /// `[list.scope]`
     ListScope(usize, Box<AST>),
+    /// `^ key = value`, insert a new dict into the list scope
+    ListInsert(Vec<AST>),
+    /// `> value`, append one value to the list scope
+    ListAppend(Box<AST>),
     /// `{dict.scope}`
     DictScope(usize, Box<AST>),
     ///
@@ -52,12 +60,20 @@ pub enum ASTKind {
     Integer(Box<Integer>),
     ///
     Decimal(Box<Decimal>),
+    /// `1979-05-27T07:32:00Z`
+    DateTime(Box<DateTime>),
     ///
     Cite(Box<AST>),
+    /// `$servers.*.pw`, `$servers/*/pw` or `${servers[?enabled].host}`, a list of all values found by the query
+    CiteQuery(String),
     ///
     Dict(Vec<AST>),
     ///
     List(Vec<AST>),
+    /// `handler[list]` or `handler{dict}` without a registered handler, the handler is kept on the value
+    Handler(String, Box<AST>),
+    /// A value computed while parsing, such as the output of a registered handler
+    Value(Box<Value>),
 }
 
 impl Debug for AST {
@@ -1,5 +1,10 @@
-use std::path::PathBuf;
-use crate::utils::parse_format;
+use crate::{
+    traits,
+    utils::parse_format,
+    value::{Definition, Layer, Provenance},
+    Result, RuntimeError, Value, AST,
+};
+use std::{fs::read_to_string, path::PathBuf};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum ExtendFormat {
@@ -14,17 +19,115 @@ pub enum ExtendFormat {
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ExtendStatement {
     format: ExtendFormat,
-    path: Option<PathBuf>,
+    path: PathBuf,
+    pub(crate) value: Value,
+    /// Where the values of the file were defined, relative to the file
+    pub(crate) provenance: Provenance,
+}
+
+/// `#merge append "path"`, sets the strategy used by the following `#extend` statements
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MergeStatement {
+    pub(crate) strategy: String,
+    pub(crate) key: Option<String>,
+    pub(crate) path: String,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ImportStatement {
+    pub(crate) source: ExtendStatement,
+    pub(crate) namespace: AST,
 }
 
 impl ExtendStatement {
-    pub fn new(_format: String, _path: String, _this: Option<PathBuf>) -> Box<Self> {
-        unimplemented!()
-        // let o = this.and_then(|e| e.parent()).map(|&e| e.join(PathBuf::from(path)));
-        // let out = Self {
-        //     format: parse_format(format),
-        //     path: o,
-        // };
-        // return box out;
-    }
-}
\ No newline at end of file
+    /// Resolve `path` relative to the directory of `this`, the file that contains the statement.
+    ///
+    /// The format comes from the `format` symbol, or from the file extension if no symbol was given.
+    pub fn new(format: String, path: String, this: Option<PathBuf>) -> Box<Self> {
+        let path = match this.as_ref().and_then(|e| e.parent()) {
+            Some(dir) => dir.join(path),
+            None => PathBuf::from(path),
+        };
+        let format = match format.is_empty() {
+            true => parse_format(path.extension().and_then(|e| e.to_str()).unwrap_or_default()),
+            false => parse_format(&format),
+        };
+        Box::new(Self { format, path, value: Value::Null, provenance: Provenance::default() })
+    }
+
+    /// Read the target file and parse it, `arc` is used for files in arc format.
+    ///
+    /// The provenance is only recorded if [`traits::ExtendFormat::explain`] is set.
+    pub fn load(&mut self, arc: &dyn traits::ExtendFormat) -> Result<()> {
+        let input = read_to_string(&self.path)?;
+        self.value = match self.format {
+            #[cfg(feature = "json")]
+            ExtendFormat::JSON => crate::utils::parse_json(&input)?,
+            #[cfg(feature = "toml")]
+            ExtendFormat::TOML => crate::utils::parse_toml(&input)?,
+            #[cfg(feature = "yaml")]
+            ExtendFormat::YAML => crate::utils::parse_yaml(&input)?,
+            ExtendFormat::ARC if arc.explain() => {
+                let (value, provenance) = arc.parse_explained(&input)?;
+                self.provenance = provenance;
+                value
+            }
+            ExtendFormat::ARC => arc.parse(&input)?,
+            ExtendFormat::TEXT => Value::from(input),
+            _ => return Err(RuntimeError::IOError(format!("unsupported format {:?}: {}", self.format, self.path.display()))),
+        };
+        // other formats have no positions, the whole file is the definition
+        if arc.explain() && self.provenance.is_empty() {
+            let definition = Definition { file: Some(self.path.clone()), range: None, layer: Layer::Document };
+            self.provenance.define_all(&[], &self.value, &definition);
+        }
+        Ok(())
+    }
+
+    pub fn get_format(&self) -> &ExtendFormat {
+        &self.format
+    }
+    pub fn get_path(&self) -> &PathBuf {
+        &self.path
+    }
+    pub fn get_value(&self) -> &Value {
+        &self.value
+    }
+    pub fn get_provenance(&self) -> &Provenance {
+        &self.provenance
+    }
+}
+
+impl ImportStatement {
+    /// Bind the file at `path` to `namespace`, the data can only be used through cites.
+    pub fn new(format: String, path: String, this: Option<PathBuf>, namespace: AST) -> Box<Self> {
+        Box::new(Self { source: *ExtendStatement::new(format, path, this), namespace })
+    }
+
+    pub fn load(&mut self, arc: &dyn traits::ExtendFormat) -> Result<()> {
+        self.source.load(arc)
+    }
+
+    pub fn get_source(&self) -> &ExtendStatement {
+        &self.source
+    }
+    pub fn get_namespace(&self) -> &AST {
+        &self.namespace
+    }
+}
+
+impl MergeStatement {
+    pub fn new(strategy: String, key: Option<String>, path: String) -> Box<Self> {
+        Box::new(Self { strategy, key, path })
+    }
+
+    pub fn get_strategy(&self) -> &str {
+        &self.strategy
+    }
+    pub fn get_key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+}
@@ -1,6 +1,7 @@
 use crate::Value;
 use indexmap::IndexMap;
-use serde_json::Number;
+use num::ToPrimitive;
+use serde_json::{Map, Number};
 use std::mem::transmute;
 
 type Json = serde_json::Value;
@@ -24,6 +25,41 @@ impl From<Json> for Value {
     }
 }
 
+/// Bytes become base64 strings, dates become RFC 3339 strings, integers beyond 64 bits become decimal strings
+/// and floats without a JSON representation become `null`.
+impl From<Value> for Json {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => Json::Null,
+            Value::Boolean(v) => Json::Bool(v),
+            Value::Integer(v) => match (v.to_i64(), v.to_u64()) {
+                (Some(n), _) => Json::from(n),
+                (_, Some(n)) => Json::from(n),
+                _ => Json::String(v.get_value().to_string()),
+            },
+            Value::Decimal(v) => Number::from_f64(v.to_f64()).map(Json::Number).unwrap_or(Json::Null),
+            Value::DateTime(v) => Json::String(v.to_string()),
+            Value::Bytes(v) => Json::String(v.to_base64()),
+            Value::String(v) => Json::String(v.as_str().to_string()),
+            Value::List(v) => {
+                let mut items = Vec::with_capacity(v.length());
+                for (holes, item) in v.runs() {
+                    items.resize(items.len() + holes, Json::Null);
+                    items.push(Json::from(item.clone()))
+                }
+                Json::Array(items)
+            }
+            Value::Dict(v) => {
+                let mut map = Map::new();
+                for (k, v) in v.iter() {
+                    map.insert(k.to_owned(), Json::from(v.clone()));
+                }
+                Json::Object(map)
+            }
+        }
+    }
+}
+
 #[allow(dead_code)]
 enum JsonNumber {
     PosInt(u64),
@@ -43,3 +79,13 @@ impl From<Number> for Value {
         }
     }
 }
+
+#[test]
+fn test_into_json() {
+    use crate::{dict, value::Bytes};
+    let value = dict! {
+        "key": Bytes::from(vec![0xde, 0xad, 0xbe, 0xef]),
+        "big": num::BigInt::from(u64::MAX) * 2
+    };
+    assert_eq!(Json::from(value).to_string(), r#"{"key":"3q2+7w==","big":"36893488147419103230"}"#);
+}
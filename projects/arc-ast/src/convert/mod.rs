@@ -5,3 +5,8 @@ mod toml;
 // mod toml_editable;
 #[cfg(feature = "yaml")]
 mod yaml;
+
+#[cfg(feature = "yaml")]
+pub(crate) use yaml::load_yaml;
+#[cfg(feature = "yaml")]
+pub use yaml::emit_yaml;
@@ -1,5 +1,6 @@
-use crate::Value;
+use crate::{value::DateTime, Value};
 use indexmap::map::IndexMap;
+use std::str::FromStr;
 
 type Toml = toml::Value;
 
@@ -10,8 +11,10 @@ impl From<Toml> for Value {
             Toml::Integer(v) => v.into(),
             Toml::Float(v) => v.into(),
             Toml::Boolean(v) => v.into(),
-            // FIXME: turn into utc
-            Toml::Datetime(v) => format!("{}", v).into(),
+            Toml::Datetime(v) => match DateTime::from_str(&v.to_string()) {
+                Ok(o) => o.into(),
+                Err(_) => v.to_string().into(),
+            },
             Toml::Array(v) => v.into(),
             Toml::Table(v) => {
                 let mut dict = IndexMap::new();
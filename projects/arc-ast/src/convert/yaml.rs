@@ -1,6 +1,15 @@
-use crate::Value;
+use crate::{
+    value::{DateTime, IeeeSpecial, List},
+    Value,
+};
 use indexmap::map::IndexMap;
-use yaml_rust::{yaml::Hash, Yaml};
+use std::{collections::HashMap, fmt::Write, mem::take, str::FromStr};
+use yaml_rust::{
+    parser::{Event, MarkedEventReceiver, Parser},
+    scanner::{Marker, TScalarStyle, TokenType},
+    yaml::Hash,
+    ScanError, Yaml,
+};
 
 pub trait ToArc {
     fn to_arc(&self) -> String;
@@ -13,14 +22,13 @@ impl From<Yaml> for Value {
             Yaml::BadValue => Value::Null,
             Yaml::Integer(v) => v.into(),
             Yaml::Real(r) => {
-                if r.to_lowercase().contains("n") {
-                    // match r.to_lowercase().as_str() {
-                    //     ".inf" | "+.inf" | "inf" => format!("{:#X}f64", f64::INFINITY.to_bits()),
-                    //     "-inf" | "-.inf" => format!("{:#X}f64", f64::NEG_INFINITY.to_bits()),
-                    //     ".nan" | "nan" => format!("{:#X}f64", f64::NAN.to_bits()),
-                    //     _ => Value::Null,
-                    // }
-                    return Value::Null;
+                // `.inf`, `-.Inf`, `.NaN`
+                let lower = r.to_lowercase();
+                if lower.contains("inf") || lower.contains("nan") {
+                    return match lower.replacen('.', "", 1).parse::<f64>() {
+                        Ok(o) => o.into(),
+                        Err(_) => Value::Null,
+                    };
                 }
                 let r = if r.starts_with('.') {
                     format!("0{}", r)
@@ -36,14 +44,13 @@ impl From<Yaml> for Value {
                     Err(_) => Value::Null,
                 }
             }
+            // a tree does not tell plain scalars from quoted ones, only [`load_yaml`] reads timestamps
             Yaml::String(v) => v.into(),
             Yaml::Boolean(v) => v.into(),
             Yaml::Array(v) => v.into(),
             Yaml::Hash(v) => v.into(),
-            Yaml::Alias(a) => {
-                println!("{:#?}", a);
-                unreachable!()
-            }
+            // anchors are resolved by the loader, an alias left here points to nothing
+            Yaml::Alias(_) => Value::Null,
         }
     }
 }
@@ -54,11 +61,279 @@ impl From<Hash> for Value {
         for (k, v) in v.iter() {
             let k = match k {
                 Yaml::Null => String::from("null"),
-                Yaml::String(s) => s.to_owned(),
-                _ => unimplemented!("{:?}", k),
+                Yaml::String(s) | Yaml::Real(s) => s.to_owned(),
+                Yaml::Integer(i) => i.to_string(),
+                Yaml::Boolean(b) => b.to_string(),
+                // complex keys have no counterpart in arc
+                _ => continue,
             };
             dict.insert(k, Value::from(v.clone()));
         }
         dict.into()
     }
 }
+
+/// Read all documents of a YAML stream, a plain scalar such as `2001-12-14` is read as timestamp and a quoted one
+/// stays a string.
+pub(crate) fn load_yaml(text: &str) -> Result<Vec<Value>, ScanError> {
+    let mut builder = YamlBuilder::default();
+    Parser::new(text.chars()).load(&mut builder, true)?;
+    Ok(builder.documents)
+}
+
+#[derive(Default)]
+struct YamlBuilder {
+    documents: Vec<Value>,
+    /// The open lists and dicts with their anchor
+    stack: Vec<(Node, usize)>,
+    anchors: HashMap<usize, Value>,
+}
+
+enum Node {
+    List(Vec<Value>),
+    /// The pairs and the key waiting for its value
+    Dict(IndexMap<String, Value>, Key),
+}
+
+#[derive(Default)]
+enum Key {
+    #[default]
+    Missing,
+    Found(String),
+    /// A list or dict as key has no counterpart in arc, the pair is dropped
+    Complex,
+}
+
+impl MarkedEventReceiver for YamlBuilder {
+    fn on_event(&mut self, event: Event, _: Marker) {
+        match event {
+            // an empty document is null
+            Event::DocumentStart => self.documents.push(Value::Null),
+            Event::SequenceStart(anchor) => self.stack.push((Node::List(vec![]), anchor)),
+            Event::MappingStart(anchor) => self.stack.push((Node::Dict(IndexMap::new(), Key::Missing), anchor)),
+            Event::SequenceEnd | Event::MappingEnd => {
+                if let Some((node, anchor)) = self.stack.pop() {
+                    self.insert(node.into_value(), anchor)
+                }
+            }
+            Event::Scalar(v, style, anchor, tag) => self.insert(yaml_scalar(v, style, tag), anchor),
+            Event::Alias(id) => self.insert(self.anchors.get(&id).cloned().unwrap_or_default(), 0),
+            _ => (),
+        }
+    }
+}
+
+impl YamlBuilder {
+    fn insert(&mut self, value: Value, anchor: usize) {
+        // valid anchors start from 1
+        if anchor > 0 {
+            self.anchors.insert(anchor, value.clone());
+        }
+        match self.stack.last_mut() {
+            None => match self.documents.last_mut() {
+                Some(root) => *root = value,
+                None => self.documents.push(value),
+            },
+            Some((Node::List(items), _)) => items.push(value),
+            Some((Node::Dict(pairs, key), _)) => match take(key) {
+                Key::Missing => *key = yaml_key(&value).map_or(Key::Complex, Key::Found),
+                Key::Found(k) => {
+                    pairs.insert(k, value);
+                }
+                Key::Complex => (),
+            },
+        }
+    }
+}
+
+impl Node {
+    fn into_value(self) -> Value {
+        match self {
+            Node::List(items) => Value::from(items),
+            Node::Dict(pairs, _) => Value::from(pairs),
+        }
+    }
+}
+
+/// Quoted scalars are strings, plain ones are resolved as by [`yaml_rust::YamlLoader`].
+fn yaml_scalar(v: String, style: TScalarStyle, tag: Option<TokenType>) -> Value {
+    if style != TScalarStyle::Plain {
+        return Value::from(v);
+    }
+    match tag {
+        Some(TokenType::Tag(handle, suffix)) if handle == "!!" && matches!(suffix.as_str(), "bool" | "int" | "float" | "null") => {
+            Value::from(Yaml::from_str(&v))
+        }
+        Some(_) => Value::from(v),
+        None => match Yaml::from_str(&v) {
+            Yaml::String(s) => match parse_timestamp(&s) {
+                Some(o) => o.into(),
+                None => s.into(),
+            },
+            other => Value::from(other),
+        },
+    }
+}
+
+fn yaml_key(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => Some(String::from("null")),
+        Value::String(s) => Some(s.as_str().to_string()),
+        Value::Boolean(v) => Some(v.to_string()),
+        Value::Integer(v) => Some(v.get_value().to_string()),
+        Value::Decimal(v) => Some(v.get_value().to_string()),
+        Value::DateTime(v) => Some(v.to_string()),
+        _ => None,
+    }
+}
+
+/// Read a YAML 1.1 timestamp, such as `2001-12-14t21:59:43.10-05:00` or `2001-12-14 21:59:43.10 -5`.
+fn parse_timestamp(s: &str) -> Option<DateTime> {
+    if !s.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    if let Ok(o) = DateTime::from_str(s) {
+        return Some(o);
+    }
+    let split = s.find(&['T', 't', ' ', '\t'][..])?;
+    let (date, rest) = (&s[..split], s[split + 1..].trim_start());
+    let mut date = date.split('-');
+    let (y, m, d) = (date.next()?, date.next()?, date.next()?);
+    if date.next().is_some() || y.len() != 4 {
+        return None;
+    }
+    let offset_at = rest.rfind(&['+', '-', 'Z', 'z'][..]);
+    let (time, offset) = match offset_at {
+        Some(i) => (rest[..i].trim_end(), &rest[i..]),
+        None => (rest, ""),
+    };
+    let mut time = time.splitn(2, ':');
+    let (h, ms) = (time.next()?, time.next()?);
+    let offset = match offset {
+        "" | "Z" | "z" => offset.to_string(),
+        _ => {
+            let mut parts = offset[1..].splitn(2, ':');
+            let (oh, om) = (parts.next()?, parts.next().unwrap_or("00"));
+            format!("{}{:0>2}:{}", &offset[..1], oh, om)
+        }
+    };
+    let normalized = format!("{}-{:0>2}-{:0>2}T{:0>2}:{}{}", y, m, d, h, ms, offset);
+    DateTime::from_str(&normalized).ok()
+}
+
+/// Write a value as block style YAML, bytes are written as `!!binary` scalars.
+pub fn emit_yaml(value: &Value) -> String {
+    let mut out = String::new();
+    match value {
+        Value::List(v) if v.length() != 0 => emit_list(v, 0, &mut out),
+        Value::Dict(v) if v.length() != 0 => emit_dict(v.iter(), 0, &mut out),
+        _ => {
+            emit_scalar(value, &mut out);
+            out.push('\n')
+        }
+    }
+    out
+}
+
+fn emit_node(value: &Value, indent: usize, out: &mut String) {
+    match value {
+        Value::List(v) if v.length() != 0 => {
+            out.push('\n');
+            emit_list(v, indent, out)
+        }
+        Value::Dict(v) if v.length() != 0 => {
+            out.push('\n');
+            emit_dict(v.iter(), indent, out)
+        }
+        _ => {
+            out.push(' ');
+            emit_scalar(value, out);
+            out.push('\n')
+        }
+    }
+}
+
+/// Holes are written as null.
+fn emit_list(list: &List, indent: usize, out: &mut String) {
+    for (holes, item) in list.runs() {
+        for _ in 0..holes {
+            writeln!(out, "{:indent$}- null", "", indent = indent).unwrap_or_default();
+        }
+        write!(out, "{:indent$}-", "", indent = indent).unwrap_or_default();
+        emit_node(item, indent + 2, out)
+    }
+}
+
+fn emit_dict<'a>(pairs: impl Iterator<Item = (&'a String, &'a Value)>, indent: usize, out: &mut String) {
+    for (key, value) in pairs {
+        write!(out, "{:indent$}", "", indent = indent).unwrap_or_default();
+        emit_string(key, out);
+        out.push(':');
+        emit_node(value, indent + 2, out)
+    }
+}
+
+fn emit_scalar(value: &Value, out: &mut String) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Boolean(v) => write!(out, "{}", v).unwrap_or_default(),
+        Value::Integer(v) => write!(out, "{}", v.get_value()).unwrap_or_default(),
+        Value::Decimal(v) => match v.get_special() {
+            Some(IeeeSpecial::Infinity) => out.push_str(".inf"),
+            Some(IeeeSpecial::NegativeInfinity) => out.push_str("-.inf"),
+            Some(IeeeSpecial::NaN) => out.push_str(".nan"),
+            Some(IeeeSpecial::NegativeZero) => out.push_str("-0.0"),
+            None => write!(out, "{}", v.get_value()).unwrap_or_default(),
+        },
+        Value::DateTime(v) => write!(out, "{}", v).unwrap_or_default(),
+        Value::Bytes(v) => write!(out, "!!binary {}", v.to_base64()).unwrap_or_default(),
+        Value::String(v) => emit_string(v.as_str(), out),
+        Value::List(_) => out.push_str("[]"),
+        Value::Dict(_) => out.push_str("{}"),
+    }
+}
+
+/// Strings are always double quoted so that they never read back as another type.
+fn emit_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04X}", c as u32).unwrap_or_default(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[test]
+fn test_emit() {
+    use crate::{dict, list, value::Bytes};
+    let value = dict! {
+        "key": Bytes::from(vec![0xde, 0xad, 0xbe, 0xef]),
+        "name": "a \"b\"",
+        "list": list![1, dict! {"x": true}],
+        "empty": list![]
+    };
+    let yaml = emit_yaml(&value);
+    assert_eq!(yaml, "\"key\": !!binary 3q2+7w==\n\"name\": \"a \\\"b\\\"\"\n\"list\":\n  - 1\n  -\n    \"x\": true\n\"empty\": []\n");
+    assert_eq!(crate::utils::parse_yaml(&yaml).unwrap().pointer("list.1.x"), Some(&Value::from(true)));
+}
+
+#[test]
+fn test_load() {
+    let text = "date: 2002-12-14\nversion: \"2002-12-14\"\nquoted: '2001-12-14 21:59:43.10 -5'\nbase: &b [1, 2]\ncopy: *b\n1: one\n? [x]\n: dropped\n";
+    let value = crate::utils::parse_yaml(text).unwrap();
+    assert!(matches!(value.pointer("date"), Some(Value::DateTime(_))));
+    assert_eq!(value.pointer("version"), Some(&Value::from("2002-12-14")));
+    assert_eq!(value.pointer("quoted"), Some(&Value::from("2001-12-14 21:59:43.10 -5")));
+    assert_eq!(value.pointer("copy"), Some(&crate::list![1, 2]));
+    assert_eq!(value.pointer("1"), Some(&Value::from("one")));
+    assert_eq!(value.pointer("x"), None);
+    let documents = crate::utils::parse_yaml("a: 1\n---\n---\n- 2\n").unwrap();
+    assert_eq!(documents, crate::list![crate::dict! {"a": 1}, Value::Null, crate::list![2]]);
+}
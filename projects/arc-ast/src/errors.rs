@@ -1,6 +1,7 @@
+use crate::TextRange;
 use std::{
     error::Error,
-    fmt::{self, Debug, Display, Formatter},
+    fmt::{self, Display, Formatter},
 };
 
 pub type Result<T> = std::result::Result<T, RuntimeError>;
@@ -9,7 +10,55 @@ pub type Result<T> = std::result::Result<T, RuntimeError>;
 pub enum RuntimeError {
     IOError(String),
     LexerError(String),
-    OtherError(Box<dyn Error>)
+    /// The input can not be parsed
+    SyntaxError(Box<Diagnostic>),
+    /// The input is parsed but can not be built into a value, such as `a = 1` followed by `a.b = 2`
+    StructureError(Box<Diagnostic>),
+    OtherError(Box<dyn Error>),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub code: ErrorCode,
+    /// 1-indexed start to end position, `None` if the value is not built from source
+    pub range: Option<TextRange>,
+    pub message: String,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ErrorCode {
+    /// The input does not match the grammar
+    InvalidSyntax = 1,
+    /// The grammar produced a node that the parser does not handle
+    UnexpectedRule = 2,
+    /// The node can not appear at this position
+    UnexpectedNode = 3,
+    /// A file used by `#extend` or `#import` can not be read or parsed
+    LoadFailed = 4,
+    /// A number literal can not be decoded, or does not fit its type suffix
+    InvalidNumber = 5,
+    /// A handler rejected its literal, such as `hex"xyz"` or a registered handler returning an error
+    HandlerFailed = 6,
+    /// An escape sequence is unknown or encodes an invalid char
+    InvalidEscape = 7,
+    /// A date or time is malformed or out of range, such as `2021-02-30`
+    InvalidDateTime = 8,
+    /// A `#merge` directive names an unknown strategy, or `union` misses its key field
+    InvalidMerge = 9,
+    /// A file extends or imports itself, directly or through other files
+    CircularExtend = 10,
+    /// A key is set under a value which is not a dict
+    NotDict = 101,
+    /// An index is set under a value which is not a list
+    NotList = 102,
+    /// A path segment is neither a key nor an index
+    InvalidKey = 103,
+    /// A value can not be converted to the requested type
+    InvalidType = 104,
+    /// A patch operation can not be applied, such as removing a path that does not exist
+    InvalidPatch = 105,
+    /// A path read with [`Value::get_as`](crate::Value::get_as) does not exist
+    MissingPath = 106,
 }
 
 type IOError = std::io::Error;
@@ -19,12 +68,56 @@ type YamlError = yaml_rust::ScanError;
 
 impl Display for RuntimeError {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        Debug::fmt(self, f)
+        match self {
+            RuntimeError::IOError(e) | RuntimeError::LexerError(e) => write!(f, "{}", e),
+            RuntimeError::SyntaxError(e) | RuntimeError::StructureError(e) => Display::fmt(e, f),
+            RuntimeError::OtherError(e) => Display::fmt(e, f),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "E{:04}", self.code as u32)?;
+        if let Some(r) = &self.range {
+            write!(f, " {:?}", r)?;
+        }
+        write!(f, ": {}", self.message)
     }
 }
 
 impl Error for RuntimeError {}
 
+impl RuntimeError {
+    pub fn syntax_error(code: ErrorCode, message: impl Into<String>, range: Option<TextRange>) -> Self {
+        Self::SyntaxError(Box::new(Diagnostic { code, range, message: message.into() }))
+    }
+    pub fn structure_error(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self::StructureError(Box::new(Diagnostic { code, range: None, message: message.into() }))
+    }
+    pub fn get_diagnostic(&self) -> Option<&Diagnostic> {
+        match self {
+            RuntimeError::SyntaxError(e) | RuntimeError::StructureError(e) => Some(e),
+            _ => None,
+        }
+    }
+    /// Errors without a diagnostic come from outside the arc source, they are reported as [`ErrorCode::LoadFailed`].
+    pub fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            RuntimeError::SyntaxError(e) | RuntimeError::StructureError(e) => *e,
+            _ => Diagnostic { code: ErrorCode::LoadFailed, range: None, message: self.to_string() },
+        }
+    }
+    /// Attach the position of the node which caused the error, unless a more precise one is known.
+    pub fn with_range(mut self, range: Option<TextRange>) -> Self {
+        match &mut self {
+            RuntimeError::SyntaxError(e) | RuntimeError::StructureError(e) if e.range.is_none() => e.range = range,
+            _ => (),
+        }
+        self
+    }
+}
+
 impl From<IOError> for RuntimeError {
     fn from(e: IOError) -> Self {
         Self::IOError(format!("{}", e))
@@ -1,5 +1,9 @@
-use crate::{Result, Value};
+use crate::{value::Bytes, ErrorCode, Result, RuntimeError, Value};
 use indexmap::IndexMap;
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+};
 
 /// The global function type definition
 pub trait Function: Sync + Send {
@@ -16,6 +20,49 @@ where
     }
 }
 
+/// Handlers for prefixed literals such as `ip"192.168.1.1"`, indexed by the prefix.
+///
+/// A handler receives the literal as the only argument and returns the validated value.
+#[derive(Clone)]
+pub struct HandlerRegistry {
+    handlers: IndexMap<String, Arc<dyn Function>>,
+}
+
+/// Decodes `b64"..."` and `hex"..."` strings into bytes, a handler inserted with the same name replaces them.
+impl Default for HandlerRegistry {
+    fn default() -> Self {
+        let mut out = Self { handlers: IndexMap::new() };
+        out.insert("b64", |args: &[Value], _: &IndexMap<String, Value>| decode_bytes("b64", &args[0], Bytes::from_base64));
+        out.insert("hex", |args: &[Value], _: &IndexMap<String, Value>| decode_bytes("hex", &args[0], Bytes::from_hex));
+        out
+    }
+}
+
+impl Debug for HandlerRegistry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_set().entries(self.handlers.keys()).finish()
+    }
+}
+
+impl HandlerRegistry {
+    pub fn insert(&mut self, name: impl Into<String>, handler: impl Function + 'static) {
+        self.handlers.insert(name.into(), Arc::new(handler));
+    }
+    pub fn get(&self, name: &str) -> Option<&dyn Function> {
+        self.handlers.get(name).map(|f| f.as_ref())
+    }
+}
+
+fn decode_bytes(name: &str, input: &Value, decode: fn(&str) -> Result<Bytes>) -> Result<Value> {
+    match input {
+        Value::String(s) => Ok(Value::from(decode(s.as_str())?)),
+        _ => {
+            let message = format!("a `{}` literal must be a string, found {}", name, input.kind_name());
+            Err(RuntimeError::syntax_error(ErrorCode::HandlerFailed, message, None))
+        }
+    }
+}
+
 #[allow(dead_code)]
 fn print_input(args: &[Value], options: &IndexMap<String, Value>) -> Result<Value> {
     println!("{:?}", args);
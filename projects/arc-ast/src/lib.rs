@@ -2,6 +2,7 @@
 #![feature(box_syntax)]
 
 pub mod ast;
+pub mod function;
 pub mod serde;
 pub mod utils;
 pub mod value;
@@ -9,10 +10,9 @@ pub mod traits;
 
 mod convert;
 mod errors;
-mod function;
 #[macro_use]
 mod macros;
 
 pub use ast::{TextRange, AST};
-pub use errors::{Result, RuntimeError};
+pub use errors::{Diagnostic, ErrorCode, Result, RuntimeError};
 pub use value::Value;
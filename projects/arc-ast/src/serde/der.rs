@@ -1,7 +1,27 @@
-use crate::Value;
+use super::{exact_f64, from_private, DATETIME_TOKEN, NUMBER_TOKEN};
+use crate::{
+    value::{invalid_type, Bytes},
+    ErrorCode, RuntimeError, Value,
+};
 use indexmap::map::IndexMap;
-use serde::{de::Visitor, Deserialize, Deserializer};
-use std::fmt;
+use num::ToPrimitive;
+use serde::{
+    de::{
+        self,
+        value::{MapDeserializer, SeqDeserializer},
+        DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, Unexpected, VariantAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserialize, Deserializer,
+};
+use std::{
+    fmt::{self, Display},
+    iter::once,
+};
+
+/// Convert a [`Value`] into any deserializable type.
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, RuntimeError> {
+    T::deserialize(value)
+}
 
 impl<'de> Deserialize<'de> for Value {
     #[inline]
@@ -43,6 +63,11 @@ impl<'de> Deserialize<'de> for Value {
                 Ok(value.into())
             }
 
+            #[inline]
+            fn visit_i128<E>(self, value: i128) -> Result<Value, E> {
+                Ok(value.into())
+            }
+
             #[inline]
             fn visit_u8<E>(self, value: u8) -> Result<Value, E> {
                 Ok(value.into())
@@ -63,6 +88,11 @@ impl<'de> Deserialize<'de> for Value {
                 Ok(value.into())
             }
 
+            #[inline]
+            fn visit_u128<E>(self, value: u128) -> Result<Value, E> {
+                Ok(value.into())
+            }
+
             #[inline]
             fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
                 Ok(value.into())
@@ -81,6 +111,16 @@ impl<'de> Deserialize<'de> for Value {
                 Ok(value.into())
             }
 
+            #[inline]
+            fn visit_bytes<E>(self, value: &[u8]) -> Result<Value, E> {
+                Ok(Bytes::from(value).into())
+            }
+
+            #[inline]
+            fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<Value, E> {
+                Ok(Bytes::from(value).into())
+            }
+
             #[inline]
             fn visit_none<E>(self) -> Result<Value, E> {
                 Ok(Value::Null)
@@ -119,8 +159,16 @@ impl<'de> Deserialize<'de> for Value {
             {
                 let mut dict: IndexMap<String, Value> = IndexMap::new();
 
-                while let Some((key, value)) = visitor.next_entry()? {
-                    dict.insert(key, value);
+                while let Some(key) = visitor.next_key::<String>()? {
+                    match key.as_str() {
+                        NUMBER_TOKEN | DATETIME_TOKEN if dict.is_empty() => {
+                            let value: String = visitor.next_value()?;
+                            return from_private(&key, &value).map_err(de::Error::custom);
+                        }
+                        _ => {
+                            dict.insert(key, visitor.next_value()?);
+                        }
+                    }
                 }
 
                 Ok(dict.into())
@@ -129,3 +177,377 @@ impl<'de> Deserialize<'de> for Value {
         deserializer.deserialize_any(ValueVisitor)
     }
 }
+
+impl serde::de::Error for RuntimeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        RuntimeError::structure_error(ErrorCode::InvalidType, msg.to_string())
+    }
+}
+
+impl<'de> IntoDeserializer<'de, RuntimeError> for Value {
+    type Deserializer = Value;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for Value {
+    type Error = RuntimeError;
+
+    /// Integers beyond 128 bits, decimals that a `f64` can not hold and datetimes are passed as a private single
+    /// entry map, so they survive a round trip into [`Value`], use [`Deserializer::deserialize_string`] to read them
+    /// as strings and [`Deserializer::deserialize_f64`] to read any decimal as `f64`.
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Null => visitor.visit_unit(),
+            Value::Boolean(v) => visitor.visit_bool(v),
+            Value::Integer(v) => {
+                if let Some(n) = v.to_i64() {
+                    visitor.visit_i64(n)
+                }
+                else if let Some(n) = v.to_u64() {
+                    visitor.visit_u64(n)
+                }
+                else if let Some(n) = v.to_i128() {
+                    visitor.visit_i128(n)
+                }
+                else if let Some(n) = v.to_u128() {
+                    visitor.visit_u128(n)
+                }
+                else {
+                    visitor.visit_map(MapDeserializer::new(once((NUMBER_TOKEN, v.get_value().to_string()))))
+                }
+            }
+            Value::Decimal(v) => match exact_f64(&v) {
+                Some(n) => visitor.visit_f64(n),
+                None => visitor.visit_map(MapDeserializer::new(once((NUMBER_TOKEN, v.get_value().to_string())))),
+            },
+            Value::DateTime(v) => visitor.visit_map(MapDeserializer::new(once((DATETIME_TOKEN, v.to_string())))),
+            Value::Bytes(v) => visitor.visit_byte_buf(v.get_value()),
+            Value::String(v) => visitor.visit_string(v.as_str().to_string()),
+            Value::List(v) => {
+                let mut seq = SeqDeserializer::new(v.slots().map(|(_, item)| item.cloned().unwrap_or_default()));
+                let out = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(out)
+            }
+            Value::Dict(v) => {
+                let pairs: Vec<(KeyDeserializer, Value)> = v.iter().map(|(k, v)| (KeyDeserializer { key: k.to_owned() }, v.to_owned())).collect();
+                let mut map = MapDeserializer::new(pairs.into_iter());
+                let out = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(out)
+            }
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Integer(v) if v.to_i128().is_none() && v.to_u128().is_none() => visitor.visit_string(v.get_value().to_string()),
+            Value::DateTime(v) => visitor.visit_string(v.to_string()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Decimal(v) => visitor.visit_f64(v.to_f64()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Bytes(v) => visitor.visit_byte_buf(v.get_value()),
+            Value::String(v) => visitor.visit_string(v.as_str().to_string()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    /// Bytes are also read as a sequence of `u8`, so they deserialize into a plain `Vec<u8>`.
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Bytes(v) => {
+                let mut seq = SeqDeserializer::<_, RuntimeError>::new(v.get_value().into_iter());
+                let out = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(out)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// A number with a time unit such as `30s` is read as `std::time::Duration`.
+    fn deserialize_struct<V>(self, name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.as_duration() {
+            Some(d) if name == "Duration" && fields == ["secs", "nanos"] => {
+                let parts = vec![Value::from(d.as_secs()), Value::from(d.subsec_nanos())];
+                let mut seq = SeqDeserializer::new(parts.into_iter());
+                let out = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(out)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    /// A unit variant is a string, `"Red"`. Other variants are named by the handler of their content,
+    /// `Circle{radius = 1}`, `Point[1, 2]` and `Named"red"`, or by the key of a dict with a single pair, `{Meters = 5}`.
+    fn deserialize_enum<V>(self, name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let (variant, content) = match self {
+            Value::String(mut v) => match v.take_handler() {
+                Some(handler) => (handler, Some(Value::String(v))),
+                None => (v.as_str().to_string(), None),
+            },
+            Value::List(mut v) => match v.take_handler() {
+                Some(handler) => (handler, Some(Value::List(v))),
+                None => return Err(invalid_type("", &format!("enum `{}`", name), &Value::List(v))),
+            },
+            Value::Dict(mut v) => match v.take_handler() {
+                Some(handler) => (handler, Some(Value::Dict(v))),
+                None if v.length() == 1 => {
+                    let (key, value) = v.iter().next().map(|(k, v)| (k.to_owned(), v.to_owned())).unwrap_or_default();
+                    (key, Some(value))
+                }
+                None => return Err(invalid_type("", &format!("enum `{}` as dict with a single key", name), &Value::Dict(v))),
+            },
+            _ => return Err(invalid_type("", &format!("enum `{}`", name), &self)),
+        };
+        visitor.visit_enum(EnumDeserializer { variant, content })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 char
+        unit unit_struct tuple tuple_struct map identifier ignored_any
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    content: Option<Value>,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+    type Error = RuntimeError;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(KeyDeserializer { key: self.variant })?;
+        Ok((variant, VariantDeserializer { content: self.content }))
+    }
+}
+
+struct VariantDeserializer {
+    content: Option<Value>,
+}
+
+impl<'de> VariantAccess<'de> for VariantDeserializer {
+    type Error = RuntimeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self.content {
+            None | Some(Value::Null) => Ok(()),
+            Some(v) => Err(invalid_type("", "unit variant", &v)),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.content {
+            Some(v) => seed.deserialize(v),
+            None => Err(de::Error::invalid_type(Unexpected::UnitVariant, &"newtype variant")),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.content {
+            Some(v) => v.deserialize_seq(visitor),
+            None => Err(de::Error::invalid_type(Unexpected::UnitVariant, &"tuple variant")),
+        }
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.content {
+            Some(v) => v.deserialize_map(visitor),
+            None => Err(de::Error::invalid_type(Unexpected::UnitVariant, &"struct variant")),
+        }
+    }
+}
+
+/// A dict key, also read as the number or boolean it spells, for maps such as `HashMap<u32, T>`.
+struct KeyDeserializer {
+    key: String,
+}
+
+impl<'de> IntoDeserializer<'de, RuntimeError> for KeyDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident),+) => {$(
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            match self.key.parse() {
+                Ok(o) => visitor.$visit(o),
+                Err(_) => visitor.visit_string(self.key),
+            }
+        }
+    )+};
+}
+
+impl<'de> Deserializer<'de> for KeyDeserializer {
+    type Error = RuntimeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.key)
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8, deserialize_i16 => visit_i16, deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64, deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8, deserialize_u16 => visit_u16, deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64, deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32, deserialize_f64 => visit_f64
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(IntoDeserializer::<RuntimeError>::into_deserializer(self.key))
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[test]
+fn test_bytes() {
+    struct ByteBuf(Vec<u8>);
+    impl<'de> Deserialize<'de> for ByteBuf {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct BytesVisitor;
+            impl<'de> Visitor<'de> for BytesVisitor {
+                type Value = ByteBuf;
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("bytes")
+                }
+                fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<ByteBuf, E> {
+                    Ok(ByteBuf(v))
+                }
+            }
+            deserializer.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+    let value = Value::from(Bytes::from(vec![0xde, 0xad]));
+    assert_eq!(ByteBuf::deserialize(value.clone()).unwrap().0, vec![0xde, 0xad]);
+    assert_eq!(Vec::<u8>::deserialize(value.clone()).unwrap(), vec![0xde, 0xad]);
+    assert_eq!(Value::deserialize(value.clone()).unwrap(), value);
+}
+
+#[test]
+fn test_decimal() {
+    use crate::value::parse_number;
+    let value = parse_number("0.10000000000000000000000001").unwrap();
+    assert_eq!(Value::deserialize(value.clone()).unwrap(), value);
+    assert!((f64::deserialize(value).unwrap() - 0.1).abs() < 1e-12);
+    let value = parse_number("1.5").unwrap();
+    assert_eq!(Value::deserialize(value.clone()).unwrap(), value);
+    assert_eq!(f32::deserialize(value).unwrap(), 1.5);
+}
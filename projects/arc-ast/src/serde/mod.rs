@@ -1,4 +1,37 @@
 mod der;
 mod ser;
 
-pub use ser::{CompactFormatter, PrettyFormatter, Serializer};
+pub use der::from_value;
+pub use ser::{
+    to_string, to_string_pretty, to_value, to_writer, to_writer_pretty, CompactFormatter, Formatter, PrettyFormatter, SectionFormatter,
+    Serializer, ValueSerializer,
+};
+
+use crate::{
+    value::{parse_number, DateTime, Decimal},
+    ErrorCode, Result, RuntimeError, Value,
+};
+use bigdecimal::BigDecimal;
+use std::str::FromStr;
+
+/// Integers and decimals that the serde data model can not hold, passed as their digits.
+pub(crate) const NUMBER_TOKEN: &str = "$arc::private::Number";
+/// Datetimes in RFC 3339, passed as string.
+pub(crate) const DATETIME_TOKEN: &str = "$arc::private::DateTime";
+
+/// Read back a value passed as a private newtype or single entry map.
+fn from_private(token: &str, s: &str) -> Result<Value> {
+    match token {
+        DATETIME_TOKEN => Ok(Value::from(DateTime::from_str(s)?)),
+        _ => parse_number(s).ok_or_else(|| RuntimeError::structure_error(ErrorCode::InvalidNumber, format!("invalid number `{}`", s))),
+    }
+}
+
+/// The decimal as `f64` if it reads back to the same digits, specials such as `inf` always fit.
+fn exact_f64(v: &Decimal) -> Option<f64> {
+    let n = v.to_f64();
+    match v.get_special() {
+        Some(_) => Some(n),
+        None => Some(n).filter(|n| BigDecimal::from_str(&n.to_string()).ok() == Some(v.get_value())),
+    }
+}
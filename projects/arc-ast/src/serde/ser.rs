@@ -1,11 +1,872 @@
-#[allow(dead_code)]
+use super::{exact_f64, from_private, DATETIME_TOKEN, NUMBER_TOKEN};
+use crate::{
+    value::{escape, is_list_scope, is_section, parse_unit, Bytes, Decimal, Dict, FromValue, List, Text, TextDelimiter},
+    ErrorCode, Result, RuntimeError, Value,
+};
+use bigdecimal::BigDecimal;
+use num::{BigInt, ToPrimitive};
+use serde::{
+    ser::{self, Impossible},
+    Serialize,
+};
+use std::{
+    fmt::{self, Display},
+    io::{self, Write},
+    str::FromStr,
+};
+
+/// Handlers are dropped, integers beyond 128 bits, decimals that a `f64` can not hold and datetimes are passed
+/// as private newtypes, so they survive a round trip through [`to_value`] and read as strings in other formats.
+impl Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Boolean(v) => serializer.serialize_bool(*v),
+            Value::Integer(v) => {
+                if let Some(n) = v.to_i64() {
+                    serializer.serialize_i64(n)
+                }
+                else if let Some(n) = v.to_u64() {
+                    serializer.serialize_u64(n)
+                }
+                else if let Some(n) = v.to_i128() {
+                    serializer.serialize_i128(n)
+                }
+                else if let Some(n) = v.to_u128() {
+                    serializer.serialize_u128(n)
+                }
+                else {
+                    serializer.serialize_newtype_struct(NUMBER_TOKEN, &v.get_value().to_string())
+                }
+            }
+            Value::Decimal(v) => match exact_f64(v) {
+                Some(n) => serializer.serialize_f64(n),
+                None => serializer.serialize_newtype_struct(NUMBER_TOKEN, &v.get_value().to_string()),
+            },
+            Value::DateTime(v) => serializer.serialize_newtype_struct(DATETIME_TOKEN, &v.to_string()),
+            Value::Bytes(v) => serializer.serialize_bytes(v),
+            Value::String(v) => serializer.serialize_str(v.as_str()),
+            Value::List(v) => serializer.collect_seq(v.slots().map(|(_, item)| item.unwrap_or(&Value::Null))),
+            Value::Dict(v) => serializer.collect_map(v.iter()),
+        }
+    }
+}
+
+impl ser::Error for RuntimeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        RuntimeError::structure_error(ErrorCode::InvalidType, msg.to_string())
+    }
+}
+
+/// Convert any serializable type into a [`Value`].
+pub fn to_value<T>(value: &T) -> Result<Value>
+where
+    T: ?Sized + Serialize,
+{
+    value.serialize(ValueSerializer)
+}
+
+/// Serializer whose output is a [`Value`].
+///
+/// - unit variants are strings, `"Red"`
+/// - the other variants put their name on the content as handler, `Circle{radius = 1}` and `Point[1, 2]`,
+///   or wrap it as `{Meters = 5}` if the content can not hold one
+/// - `std::time::Duration` is written with a time unit, `30s`
+pub struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = RuntimeError;
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeList;
+    type SerializeMap = SerializeDict;
+    type SerializeStruct = SerializeDict;
+    type SerializeStructVariant = SerializeDict;
+
+    fn serialize_bool(self, v: bool) -> Result<Value> {
+        Ok(Value::from(v))
+    }
+    fn serialize_i8(self, v: i8) -> Result<Value> {
+        Ok(Value::from(v))
+    }
+    fn serialize_i16(self, v: i16) -> Result<Value> {
+        Ok(Value::from(v))
+    }
+    fn serialize_i32(self, v: i32) -> Result<Value> {
+        Ok(Value::from(v))
+    }
+    fn serialize_i64(self, v: i64) -> Result<Value> {
+        Ok(Value::from(v))
+    }
+    fn serialize_i128(self, v: i128) -> Result<Value> {
+        Ok(Value::from(v))
+    }
+    fn serialize_u8(self, v: u8) -> Result<Value> {
+        Ok(Value::from(v))
+    }
+    fn serialize_u16(self, v: u16) -> Result<Value> {
+        Ok(Value::from(v))
+    }
+    fn serialize_u32(self, v: u32) -> Result<Value> {
+        Ok(Value::from(v))
+    }
+    fn serialize_u64(self, v: u64) -> Result<Value> {
+        Ok(Value::from(v))
+    }
+    fn serialize_u128(self, v: u128) -> Result<Value> {
+        Ok(Value::from(v))
+    }
+    fn serialize_f32(self, v: f32) -> Result<Value> {
+        Ok(float_value(v.to_string(), f64::from(v)))
+    }
+    fn serialize_f64(self, v: f64) -> Result<Value> {
+        Ok(float_value(v.to_string(), v))
+    }
+    fn serialize_char(self, v: char) -> Result<Value> {
+        Ok(Value::from(v))
+    }
+    fn serialize_str(self, v: &str) -> Result<Value> {
+        Ok(Value::from(v))
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value> {
+        Ok(Value::from(Bytes::from(v)))
+    }
+    fn serialize_none(self) -> Result<Value> {
+        Ok(Value::Null)
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<Value> {
+        Ok(Value::Null)
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value> {
+        Ok(Value::Null)
+    }
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<Value> {
+        Ok(Value::from(variant))
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, name: &'static str, value: &T) -> Result<Value> {
+        match name {
+            NUMBER_TOKEN | DATETIME_TOKEN => match value.serialize(self)? {
+                Value::String(s) => from_private(name, s.as_str()),
+                v => Err(ser::Error::custom(format!("invalid {}: {:?}", name, v))),
+            },
+            _ => value.serialize(self),
+        }
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        Ok(tagged(variant, to_value(value)?))
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList> {
+        Ok(SerializeList { handler: None, items: Vec::with_capacity(len.unwrap_or(0)) })
+    }
+    fn serialize_tuple(self, len: usize) -> Result<SerializeList> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeList> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, variant: &'static str, len: usize) -> Result<SerializeList> {
+        Ok(SerializeList { handler: Some(variant), items: Vec::with_capacity(len) })
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeDict> {
+        Ok(SerializeDict { name: "", handler: None, key: None, dict: Dict::default() })
+    }
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<SerializeDict> {
+        Ok(SerializeDict { name, handler: None, key: None, dict: Dict::default() })
+    }
+    fn serialize_struct_variant(self, name: &'static str, _index: u32, variant: &'static str, _len: usize) -> Result<SerializeDict> {
+        Ok(SerializeDict { name, handler: Some(variant), key: None, dict: Dict::default() })
+    }
+}
+
+/// Floats keep their shortest digits, `0.1` rather than the exact binary expansion.
+fn float_value(shortest: String, v: f64) -> Value {
+    match BigDecimal::from_str(&shortest) {
+        Ok(o) if v.is_finite() && !(v == 0.0 && v.is_sign_negative()) => Value::from(o),
+        _ => Value::from(v),
+    }
+}
+
+/// Put the variant name on the content as handler, or wrap it in a dict with a single key.
+fn tagged(variant: &str, mut value: Value) -> Value {
+    match &mut value {
+        Value::String(v) if v.get_handler().is_none() => v.set_handler(variant),
+        Value::List(v) if v.get_handler().is_none() => v.set_handler(variant),
+        Value::Dict(v) if v.get_handler().is_none() => v.set_handler(variant),
+        _ => {
+            let mut dict = Dict::default();
+            dict.insert(variant.to_string(), value);
+            return Value::from(dict);
+        }
+    }
+    value
+}
+
+pub struct SerializeList {
+    handler: Option<&'static str>,
+    items: Vec<Value>,
+}
+
+impl SerializeList {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.items.push(to_value(value)?);
+        Ok(())
+    }
+    fn finish(self) -> Result<Value> {
+        let mut list = List::from(self.items);
+        if let Some(s) = self.handler {
+            list.set_handler(s)
+        }
+        Ok(Value::from(list))
+    }
+}
+
+impl ser::SerializeSeq for SerializeList {
+    type Ok = Value;
+    type Error = RuntimeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeList {
+    type Ok = Value;
+    type Error = RuntimeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+    type Ok = Value;
+    type Error = RuntimeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeList {
+    type Ok = Value;
+    type Error = RuntimeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+pub struct SerializeDict {
+    /// Name of the struct, empty for maps
+    name: &'static str,
+    handler: Option<&'static str>,
+    key: Option<String>,
+    dict: Dict,
+}
+
+impl SerializeDict {
+    fn insert<T: ?Sized + Serialize>(&mut self, key: &str, value: &T) -> Result<()> {
+        self.dict.insert(key.to_string(), to_value(value)?);
+        Ok(())
+    }
+    fn finish(mut self) -> Result<Value> {
+        if self.name == "Duration" {
+            if let Some(v) = duration_value(&self.dict) {
+                return Ok(v);
+            }
+        }
+        if let Some(s) = self.handler {
+            self.dict.set_handler(s)
+        }
+        Ok(Value::from(self.dict))
+    }
+}
+
+/// `std::time::Duration` serializes as `{secs, nanos}`, it is written in the largest exact time unit instead.
+fn duration_value(dict: &Dict) -> Option<Value> {
+    if dict.length() != 2 {
+        return None;
+    }
+    let secs = u64::from_value(dict.get("secs")?, "").ok()?;
+    let nanos = u32::from_value(dict.get("nanos")?, "").ok()?;
+    let total = BigInt::from(secs) * 1_000_000_000u64 + nanos;
+    parse_unit(&total.to_string(), "ns")?.ok()?.normalize_unit().map(Value::from)
+}
+
+impl ser::SerializeMap for SerializeDict {
+    type Ok = Value;
+    type Error = RuntimeError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        match self.key.take() {
+            Some(key) => self.insert(&key, value),
+            None => Err(ser::Error::custom("a dict value is serialized before its key")),
+        }
+    }
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeDict {
+    type Ok = Value;
+    type Error = RuntimeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        self.insert(key, value)
+    }
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeDict {
+    type Ok = Value;
+    type Error = RuntimeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        self.insert(key, value)
+    }
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+/// Dict keys are strings, numbers, booleans and unit variants are written as their text.
+struct KeySerializer;
+
+fn invalid_key() -> RuntimeError {
+    RuntimeError::structure_error(ErrorCode::InvalidKey, "a dict key must be a string, number, boolean or unit variant")
+}
+
+macro_rules! key_to_string {
+    ($($method:ident: $T:ty),+) => {$(
+        fn $method(self, v: $T) -> Result<String> {
+            Ok(v.to_string())
+        }
+    )+};
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = RuntimeError;
+    type SerializeSeq = Impossible<String, RuntimeError>;
+    type SerializeTuple = Impossible<String, RuntimeError>;
+    type SerializeTupleStruct = Impossible<String, RuntimeError>;
+    type SerializeTupleVariant = Impossible<String, RuntimeError>;
+    type SerializeMap = Impossible<String, RuntimeError>;
+    type SerializeStruct = Impossible<String, RuntimeError>;
+    type SerializeStructVariant = Impossible<String, RuntimeError>;
+
+    key_to_string! {
+        serialize_bool: bool,
+        serialize_i8: i8, serialize_i16: i16, serialize_i32: i32, serialize_i64: i64, serialize_i128: i128,
+        serialize_u8: u8, serialize_u16: u16, serialize_u32: u32, serialize_u64: u64, serialize_u128: u128,
+        serialize_f32: f32, serialize_f64: f64,
+        serialize_char: char, serialize_str: &str
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<String> {
+        Err(invalid_key())
+    }
+    fn serialize_none(self) -> Result<String> {
+        Err(invalid_key())
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<String> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<String> {
+        Err(invalid_key())
+    }
+    fn serialize_unit_struct(self, _: &'static str) -> Result<String> {
+        Err(invalid_key())
+    }
+    fn serialize_unit_variant(self, _: &'static str, _: u32, variant: &'static str) -> Result<String> {
+        Ok(variant.to_string())
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Result<String> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _: &'static str, _: u32, _: &'static str, _: &T) -> Result<String> {
+        Err(invalid_key())
+    }
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(invalid_key())
+    }
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple> {
+        Err(invalid_key())
+    }
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeTupleStruct> {
+        Err(invalid_key())
+    }
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeTupleVariant> {
+        Err(invalid_key())
+    }
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(invalid_key())
+    }
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct> {
+        Err(invalid_key())
+    }
+    fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeStructVariant> {
+        Err(invalid_key())
+    }
+}
+
+/// Writes a [`Value`] as arc text.
 pub struct Serializer<W, F = CompactFormatter> {
     writer: W,
     formatter: F,
 }
 
-#[derive(Clone, Debug)]
+/// Layout of documents and literals.
+pub trait Formatter {
+    /// Written after the opening bracket and between items, `depth` is 1 for items of a top level literal.
+    fn begin_item<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool, depth: usize) -> io::Result<()>;
+    /// Written before the closing bracket of a literal with items.
+    fn end_items<W: ?Sized + Write>(&mut self, writer: &mut W, depth: usize) -> io::Result<()>;
+    /// Write dicts as `{scope}` sections and lists of dicts as `[list]` scopes instead of literals.
+    fn use_scopes(&self) -> bool {
+        false
+    }
+}
+
+/// A pair per line, literals on a single line, `{a = 1, b = [2, 3]}`.
+#[derive(Clone, Debug, Default)]
 pub struct CompactFormatter;
 
-#[derive(Clone, Debug)]
+/// An item per line for literals that hold a dict or list, indented by 4 spaces.
+#[derive(Clone, Debug, Default)]
 pub struct PrettyFormatter;
+
+/// As [`PrettyFormatter`], with the dicts and lists of dicts at the end of a dict written as sections.
+#[derive(Clone, Debug, Default)]
+pub struct SectionFormatter;
+
+impl Formatter for CompactFormatter {
+    fn begin_item<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool, _: usize) -> io::Result<()> {
+        match first {
+            true => Ok(()),
+            false => writer.write_all(b", "),
+        }
+    }
+    fn end_items<W: ?Sized + Write>(&mut self, _: &mut W, _: usize) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Formatter for PrettyFormatter {
+    fn begin_item<W: ?Sized + Write>(&mut self, writer: &mut W, _: bool, depth: usize) -> io::Result<()> {
+        write!(writer, "\n{}", "    ".repeat(depth))
+    }
+    fn end_items<W: ?Sized + Write>(&mut self, writer: &mut W, depth: usize) -> io::Result<()> {
+        write!(writer, "\n{}", "    ".repeat(depth - 1))
+    }
+}
+
+impl Formatter for SectionFormatter {
+    fn begin_item<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool, depth: usize) -> io::Result<()> {
+        PrettyFormatter.begin_item(writer, first, depth)
+    }
+    fn end_items<W: ?Sized + Write>(&mut self, writer: &mut W, depth: usize) -> io::Result<()> {
+        PrettyFormatter.end_items(writer, depth)
+    }
+    fn use_scopes(&self) -> bool {
+        true
+    }
+}
+
+/// A bare string runs to the end of the line or item, it must not read as any other value.
+fn is_bare(s: &str) -> bool {
+    let word = s.split(|c: char| !(c.is_alphanumeric() || c == '_')).next().unwrap_or_default().to_ascii_lowercase();
+    s.starts_with(char::is_alphabetic)
+        && !s.ends_with(' ')
+        && s.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.' || c == ' ')
+        && !["true", "false", "null", "inf", "infinity", "nan"].contains(&word.as_str())
+}
+
+/// A lone symbol written bare would take a following `"x"`, `[x]` or `{x}` as its handler.
+fn is_symbol(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) => (c.is_alphabetic() || c == '_') && chars.all(|c| c.is_alphanumeric() || c == '_'),
+        None => false,
+    }
+}
+
+/// Whether a value is written starting with a quote or bracket.
+fn opens_with_bracket(value: &Value) -> bool {
+    match value {
+        Value::String(v) => v.get_handler().is_none(),
+        Value::List(v) => v.get_handler().is_none(),
+        Value::Dict(v) => v.get_handler().is_none(),
+        _ => false,
+    }
+}
+
+/// Whether `s` fits between `n` quotes on a single line, such as `'''C:\path'''`.
+fn fits_quotes(s: &str, quote: char, n: usize) -> bool {
+    !s.is_empty() && !s.contains('\n') && !s.starts_with(quote) && !s.ends_with(quote) && !s.contains(&quote.to_string().repeat(n))
+}
+
+impl<W: Write> Serializer<W> {
+    pub fn new(writer: W) -> Self {
+        Serializer::with_formatter(writer, CompactFormatter)
+    }
+}
+
+impl<W: Write> Serializer<W, PrettyFormatter> {
+    pub fn pretty(writer: W) -> Self {
+        Serializer::with_formatter(writer, PrettyFormatter)
+    }
+}
+
+impl<W: Write, F: Formatter> Serializer<W, F> {
+    pub fn with_formatter(writer: W, formatter: F) -> Self {
+        Serializer { writer, formatter }
+    }
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+    /// Write a dict as a document of `key = value` pairs, the handler of the root is dropped.
+    ///
+    /// With scopes only the values after the last pair become sections, so the keys keep their order.
+    pub fn write_document(&mut self, value: &Value) -> Result<()> {
+        let dict = match value {
+            Value::Dict(v) => v,
+            _ => return Err(RuntimeError::structure_error(ErrorCode::InvalidType, format!("expected dict as document, found {}", value.kind_name()))),
+        };
+        let sections = self.sections(dict);
+        let written = self.write_pairs(dict, 0, !matches!(sections.first(), Some((_, v)) if v.is_list()))?;
+        self.write_sections(&sections, 0, written, true)
+    }
+    /// Write a value as literal, such as `[1, 2]` or `Circle{radius = 1}`.
+    pub fn write_literal(&mut self, value: &Value) -> Result<()> {
+        self.write_value(value, 0)
+    }
+    /// The values at the end of a dict that are written as sections, none without scopes.
+    ///
+    /// A section runs to the next head, a dict or list followed by a plain pair is written as literal.
+    fn sections<'a>(&self, dict: &'a Dict) -> Vec<(&'a String, &'a Value)> {
+        if !self.formatter.use_scopes() {
+            return vec![];
+        }
+        let mut out: Vec<_> = dict.iter().rev().take_while(|(_, v)| is_section(v) || is_list_scope(v)).collect();
+        out.reverse();
+        out
+    }
+    /// The pairs of a dict which are not sections, returns whether any pair is written.
+    ///
+    /// `follow` tells if the line after the pairs can not continue a bare value.
+    fn write_pairs(&mut self, dict: &Dict, level: usize, follow: bool) -> Result<bool> {
+        let pairs: Vec<_> = dict.iter().take(dict.length() - self.sections(dict).len()).collect();
+        for (i, (key, value)) in pairs.iter().enumerate() {
+            let follow = pairs.get(i + 1).map_or(follow, |(key, _)| is_symbol(key));
+            write!(self.writer, "{}", "    ".repeat(level))?;
+            self.write_pair(key, value, level, follow)?;
+            self.writer.write_all(b"\n")?;
+        }
+        Ok(!pairs.is_empty())
+    }
+    /// Sections under `level` dots, `{.key}` is a child of the previous section with one dot less.
+    fn write_sections(&mut self, sections: &[(&String, &Value)], level: usize, mut blank: bool, follow: bool) -> Result<()> {
+        let pad = "    ".repeat(level);
+        for (i, (key, value)) in sections.iter().enumerate() {
+            // a `{scope}` head can not continue a bare value, a `[list]` head reads as list literal
+            let follow = sections.get(i + 1).map_or(follow, |(_, v)| v.is_dict());
+            if blank {
+                self.writer.write_all(b"\n")?;
+            }
+            blank = true;
+            match value {
+                Value::Dict(v) => {
+                    write!(self.writer, "{}{{{}", pad, ".".repeat(level))?;
+                    self.write_key(key)?;
+                    self.writer.write_all(b"}\n")?;
+                    let children = self.sections(v);
+                    let first = match children.first() {
+                        Some((_, v)) => v.is_dict(),
+                        None => follow,
+                    };
+                    self.write_pairs(v, level, first)?;
+                    self.write_sections(&children, level + 1, true, follow)?;
+                }
+                Value::List(v) => {
+                    write!(self.writer, "{}[{}", pad, ".".repeat(level))?;
+                    self.write_key(key)?;
+                    self.writer.write_all(b"]\n")?;
+                    for (j, item) in v.slots() {
+                        let item = item.unwrap_or(&Value::Null);
+                        // `^` and `>` can not continue a bare value
+                        let last = j + 1 == v.length();
+                        match item {
+                            Value::Dict(dict) if is_section(item) => {
+                                let pairs: Vec<_> = dict.iter().collect();
+                                for (k, (key, value)) in pairs.iter().enumerate() {
+                                    let follow = match pairs.get(k + 1) {
+                                        Some((key, _)) => is_symbol(key),
+                                        None => !last || follow,
+                                    };
+                                    write!(self.writer, "{}{}", pad, if k == 0 { "^ " } else { "  " })?;
+                                    self.write_pair(key, value, level, follow)?;
+                                    self.writer.write_all(b"\n")?;
+                                }
+                            }
+                            Value::String(v) => {
+                                write!(self.writer, "{}> ", pad)?;
+                                self.write_text(v, !last || follow || !is_symbol(v.as_str()), level)?;
+                                self.writer.write_all(b"\n")?;
+                            }
+                            _ => {
+                                write!(self.writer, "{}> ", pad)?;
+                                self.write_value(item, level)?;
+                                self.writer.write_all(b"\n")?;
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+    fn write_pair(&mut self, key: &str, value: &Value, level: usize, follow: bool) -> Result<()> {
+        self.write_key(key)?;
+        self.writer.write_all(b" = ")?;
+        match value {
+            Value::String(v) => self.write_text(v, follow || !is_symbol(v.as_str()), level),
+            _ => self.write_value(value, level),
+        }
+    }
+    /// Keys which are not a symbol are quoted.
+    fn write_key(&mut self, key: &str) -> Result<()> {
+        if key.is_empty() {
+            return Err(RuntimeError::structure_error(ErrorCode::InvalidKey, "an empty key can not be written"));
+        }
+        match is_symbol(key) {
+            true => write!(self.writer, "{}", key)?,
+            false => write!(self.writer, "\"{}\"", escape(key, false))?,
+        }
+        Ok(())
+    }
+    /// Write a string with its own delimiter if that delimiter can hold it, with `"` otherwise.
+    ///
+    /// `bare` is set where a bare string would end, at the end of a line or item.
+    fn write_text(&mut self, text: &Text, bare: bool, level: usize) -> Result<()> {
+        let (s, handler) = (text.as_str(), text.get_handler().unwrap_or_default());
+        // the parser turns tabs into spaces, raw strings can not hold them
+        let raw = !s.contains(&['\t', '\r'][..]);
+        match text.delimiter {
+            TextDelimiter::Bare if bare && handler.is_empty() && is_bare(s) => write!(self.writer, "{}", s)?,
+            TextDelimiter::Quotation(n) if n > 1 && fits_quotes(s, '"', n) => {
+                let quotes = "\"".repeat(n);
+                write!(self.writer, "{}{}{}{}", handler, quotes, escape(s, true), quotes)?
+            }
+            TextDelimiter::Quotation(n) if n > 1 && !s.contains(&"\"".repeat(n)) => {
+                self.write_block(&handler, &"\"".repeat(n), &escape(s, true), level)?
+            }
+            TextDelimiter::Apostrophe(1) if raw && !s.contains('\'') => write!(self.writer, "{}'{}'", handler, s)?,
+            TextDelimiter::Apostrophe(n) if n > 1 && raw && fits_quotes(s, '\'', n) => {
+                let quotes = "'".repeat(n);
+                write!(self.writer, "{}{}{}{}", handler, quotes, s, quotes)?
+            }
+            TextDelimiter::Apostrophe(n) if n > 1 && raw && !s.contains(&"'".repeat(n)) => self.write_block(&handler, &"'".repeat(n), s, level)?,
+            _ => write!(self.writer, "{}\"{}\"", handler, escape(s, false))?,
+        }
+        Ok(())
+    }
+    /// A multiline string with the delimiters on their own lines, the indentation of the closing one is removed.
+    fn write_block(&mut self, handler: &str, quotes: &str, body: &str, level: usize) -> Result<()> {
+        let pad = "    ".repeat(level);
+        writeln!(self.writer, "{}{}", handler, quotes)?;
+        for line in body.split('\n') {
+            writeln!(self.writer, "{}{}", pad, line)?;
+        }
+        write!(self.writer, "{}{}", pad, quotes)?;
+        Ok(())
+    }
+    fn write_value(&mut self, value: &Value, depth: usize) -> Result<()> {
+        match value {
+            Value::Null => write!(self.writer, "null")?,
+            Value::Boolean(v) => write!(self.writer, "{}", v)?,
+            Value::Integer(v) => write!(self.writer, "{}", v)?,
+            Value::Decimal(v) => match v.get_special() {
+                // specials are written without their handler, `inf` and `nan` can not take a suffix
+                Some(special) => write!(self.writer, "{}", Decimal::from(special))?,
+                None => {
+                    let mut digits = v.get_value().to_string();
+                    if !digits.contains(&['.', 'e', 'E'][..]) {
+                        digits.push_str(".0")
+                    }
+                    write!(self.writer, "{}{}", digits, v.get_handler().unwrap_or_default())?
+                }
+            },
+            Value::DateTime(v) => write!(self.writer, "{}", v)?,
+            Value::Bytes(v) => write!(self.writer, "{}", v)?,
+            Value::String(v) => self.write_text(v, false, depth)?,
+            Value::List(v) => {
+                write!(self.writer, "{}[", v.get_handler().unwrap_or_default())?;
+                let inline = v.iter().all(|(_, v)| !v.is_list() && !v.is_dict());
+                let mut items = v.slots().peekable();
+                while let Some((i, item)) = items.next() {
+                    self.begin_item(inline, i == 0, depth + 1)?;
+                    match item.unwrap_or(&Value::Null) {
+                        Value::String(v) => {
+                            let follow = inline || !matches!(items.peek(), Some((_, Some(next))) if opens_with_bracket(next));
+                            self.write_text(v, follow || !is_symbol(v.as_str()), depth + 1)?
+                        }
+                        item => self.write_value(item, depth + 1)?,
+                    }
+                }
+                if v.length() != 0 && !inline {
+                    self.formatter.end_items(&mut self.writer, depth + 1)?;
+                }
+                write!(self.writer, "]")?
+            }
+            Value::Dict(v) => {
+                write!(self.writer, "{}{{", v.get_handler().unwrap_or_default())?;
+                let inline = v.iter().all(|(_, v)| !v.is_list() && !v.is_dict());
+                let pairs: Vec<_> = v.iter().collect();
+                for (i, (key, item)) in pairs.iter().enumerate() {
+                    self.begin_item(inline, i == 0, depth + 1)?;
+                    self.write_key(key)?;
+                    self.writer.write_all(b" = ")?;
+                    match item {
+                        // a bare string in a dict literal ends at a newline only
+                        Value::String(v) => {
+                            let follow = !matches!(pairs.get(i + 1), Some((key, _)) if !is_symbol(key)) || !is_symbol(v.as_str());
+                            self.write_text(v, !inline && self.formatter.use_scopes() && follow, depth + 1)?
+                        }
+                        _ => self.write_value(item, depth + 1)?,
+                    }
+                }
+                if v.length() != 0 && !inline {
+                    self.formatter.end_items(&mut self.writer, depth + 1)?;
+                }
+                write!(self.writer, "}}")?
+            }
+        }
+        Ok(())
+    }
+    /// Literals of scalars stay on a single line.
+    fn begin_item(&mut self, inline: bool, first: bool, depth: usize) -> Result<()> {
+        match inline {
+            true => CompactFormatter.begin_item(&mut self.writer, first, depth)?,
+            false => self.formatter.begin_item(&mut self.writer, first, depth)?,
+        }
+        Ok(())
+    }
+}
+
+/// Arc source, a dict is written as document with sections, any other value as literal.
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut buffer = vec![];
+        let mut serializer = Serializer::with_formatter(&mut buffer, SectionFormatter);
+        let out = match self {
+            Value::Dict(_) => serializer.write_document(self),
+            _ => serializer.write_literal(self),
+        };
+        out.map_err(|_| fmt::Error)?;
+        f.write_str(&String::from_utf8_lossy(&buffer))
+    }
+}
+
+/// Serialize a struct or map as arc text, with literals on a single line.
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: Write,
+    T: ?Sized + Serialize,
+{
+    Serializer::new(writer).write_document(&to_value(value)?)
+}
+
+/// Serialize a struct or map as arc text, with an item per line.
+pub fn to_writer_pretty<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: Write,
+    T: ?Sized + Serialize,
+{
+    Serializer::pretty(writer).write_document(&to_value(value)?)
+}
+
+pub fn to_string<T>(value: &T) -> Result<String>
+where
+    T: ?Sized + Serialize,
+{
+    let mut buffer = vec![];
+    to_writer(&mut buffer, value)?;
+    String::from_utf8(buffer).map_err(|e| RuntimeError::IOError(e.to_string()))
+}
+
+pub fn to_string_pretty<T>(value: &T) -> Result<String>
+where
+    T: ?Sized + Serialize,
+{
+    let mut buffer = vec![];
+    to_writer_pretty(&mut buffer, value)?;
+    String::from_utf8(buffer).map_err(|e| RuntimeError::IOError(e.to_string()))
+}
+
+#[test]
+fn test_serialize() {
+    use crate::{dict, list};
+    let big = Value::from(BigInt::from(u128::MAX) * 10);
+    let data = dict! {
+        "name": "arc",
+        "big": big.clone(),
+        "ratio": 0.5,
+        "tags": list!["a", "b"],
+        "server": dict! {"host": "localhost", "port": 8080},
+        "a.b": Value::Null
+    };
+    assert_eq!(to_value(&big).unwrap(), big);
+    assert_eq!(to_value(&data).unwrap(), data);
+    assert_eq!(to_value(&0.1f32).unwrap(), Value::from(BigDecimal::from_str("0.1").unwrap()));
+    assert_eq!(
+        to_string(&data).unwrap(),
+        "name = \"arc\"\nbig = 3402823669209384634633746074317682114550\nratio = 0.5\ntags = [\"a\", \"b\"]\nserver = {host = \"localhost\", port = 8080}\n\"a.b\" = null\n"
+    );
+    assert_eq!(to_string_pretty(&dict! {"tags": list![1, list![]]}).unwrap(), "tags = [\n    1\n    []\n]\n");
+    // sections only at the end, the keys keep their order
+    assert_eq!(
+        dict! {"server": dict! {"host": "localhost", "port": 8080}, "a.b": Value::Null}.to_string(),
+        "server = {host = \"localhost\", port = 8080}\n\"a.b\" = null\n"
+    );
+    assert_eq!(
+        dict! {"name": "arc", "server": dict! {"host": "localhost"}, "tags": list![1, list![]]}.to_string(),
+        "name = \"arc\"\n\n{server}\nhost = \"localhost\"\n\n[tags]\n> 1\n> []\n"
+    );
+    assert_eq!(list![1, dict! {"a": list![2]}].to_string(), "[\n    1\n    {\n        a = [2]\n    }\n]");
+    assert!(to_string(&list![1]).is_err());
+}
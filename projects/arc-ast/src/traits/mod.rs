@@ -1,5 +1,13 @@
-use crate::{Result, Value};
+use crate::{value::Provenance, Result, Value};
 
 pub trait ExtendFormat {
     fn parse(&self, input: &str) -> Result<Value>;
+    /// Parse and record where each value was defined, formats without source positions record nothing.
+    fn parse_explained(&self, input: &str) -> Result<(Value, Provenance)> {
+        Ok((self.parse(input)?, Provenance::default()))
+    }
+    /// Whether the files loaded with this format should record where their values were defined.
+    fn explain(&self) -> bool {
+        false
+    }
 }
\ No newline at end of file
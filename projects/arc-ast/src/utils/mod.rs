@@ -11,6 +11,8 @@ pub use wrap_parser::parse_json;
 pub use wrap_parser::parse_toml;
 #[cfg(feature = "yaml")]
 pub use wrap_parser::parse_yaml;
+#[cfg(feature = "yaml")]
+pub use crate::convert::emit_yaml;
 use crate::ast::ExtendFormat;
 
 
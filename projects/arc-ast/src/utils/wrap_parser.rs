@@ -14,9 +14,9 @@ pub fn parse_toml(toml: &str) -> Result<Value> {
 
 #[cfg(feature = "yaml")]
 pub fn parse_yaml(yaml: &str) -> Result<Value> {
-    let out = yaml_rust::YamlLoader::load_from_str(&yaml)?;
+    let mut out = crate::convert::load_yaml(yaml)?;
     let value = match out.len() {
-        1 => Value::from(out[0].clone()),
+        1 => out.remove(0),
         _ => Value::from(out),
     };
     Ok(value)
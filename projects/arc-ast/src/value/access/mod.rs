@@ -1,40 +1,37 @@
 use super::*;
 
+mod path;
+mod query;
+
+pub use path::parse_path;
+pub use query::{Query, QueryMatch};
+
 impl Value {
-    /// Merge in a configuration property source.
-    pub fn merge(&mut self, incoming: Value) {
-        if !self.is_dict() || !incoming.is_dict() {
-            return *self = incoming;
-        }
-        match (self, incoming) {
-            (Value::List(lhs), Value::List(rhs)) => **lhs += *rhs,
-            (Value::Dict(lhs), Value::Dict(rhs)) => **lhs += *rhs,
-            _ => unreachable!(),
+    pub fn as_datetime(&self) -> Option<&DateTime> {
+        match self {
+            Value::DateTime(v) => Some(v),
+            _ => None,
         }
     }
-
-    pub fn pointer(&self, path: &str) -> Option<&Value> {
-        if path.is_empty() {
-            return Some(self);
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(v) => Some(v),
+            _ => None,
         }
-        let mut target = self;
-        for token in path.split('.') {
-            let try_get = match target {
-                Value::Dict(dict) => dict.get(&token),
-                Value::List(list) => list.get(&token),
-                _ => return None,
-            };
-            if let None = try_get.map(|t| target = t) {
-                return None;
-            }
+    }
+    /// Numbers with a time unit, such as `30s` or `1h30m`.
+    pub fn as_duration(&self) -> Option<std::time::Duration> {
+        match self {
+            Value::Integer(v) => v.as_duration(),
+            _ => None,
         }
-        Some(target)
     }
-    pub fn pointer_mut(&mut self, path: &str) -> Option<&mut Value> {
-        if path.is_empty() {
-            return Some(self);
+    /// Numbers with a byte unit in bytes, such as `10MB` or `512MiB`.
+    pub fn as_byte_size(&self) -> Option<u64> {
+        match self {
+            Value::Integer(v) => v.as_byte_size(),
+            _ => None,
         }
-        unimplemented!()
     }
 
     pub fn as_vec(&self) -> Vec<Value> {
@@ -43,6 +40,8 @@ impl Value {
             Value::Boolean(_) => vec![self.to_owned()],
             Value::Integer(_) => vec![self.to_owned()],
             Value::Decimal(_) => vec![self.to_owned()],
+            Value::DateTime(_) => vec![self.to_owned()],
+            Value::Bytes(_) => vec![self.to_owned()],
             Value::String(_) => vec![self.to_owned()],
             Value::List(v) => v.as_vec(),
             Value::Dict(v) => v.as_vec(),
@@ -55,6 +54,8 @@ impl Value {
             Value::Boolean(v) => vec![format!("{}", v)],
             Value::Integer(v) => vec![format!("{}", v)],
             Value::Decimal(v) => vec![format!("{}", v)],
+            Value::DateTime(v) => vec![format!("{}", v)],
+            Value::Bytes(v) => vec![format!("{}", v)],
             Value::String(v) => vec![format!("{:?}", v)],
             Value::List(v) => {
                 let mut vec = Vec::with_capacity(v.length());
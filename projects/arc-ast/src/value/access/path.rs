@@ -0,0 +1,182 @@
+use super::*;
+
+/// Split a path into its segments, the path is either dotted or a JSON pointer.
+///
+/// - `a.b.0` and `a."b.c".-1`, a quoted segment may contain dots and escapes
+/// - `/a/b/0` and `/a/b~1c/-` as in RFC 6901, `~1` is `/` and `~0` is `~`
+///
+/// The empty path points to the root. A segment is used as key under a dict and as index under a list.
+pub fn parse_path(path: &str) -> Result<Vec<String>> {
+    if path.is_empty() {
+        return Ok(vec![]);
+    }
+    match path.starts_with('/') {
+        true => path[1..].split('/').map(|token| unescape_pointer(path, token)).collect(),
+        false => split_dotted(path),
+    }
+}
+
+pub(super) fn unescape_pointer(path: &str, token: &str) -> Result<String> {
+    let mut out = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '~' => match chars.next() {
+                Some('0') => out.push('~'),
+                Some('1') => out.push('/'),
+                _ => return Err(invalid_path(path, "`~` must be followed by `0` or `1`")),
+            },
+            _ => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+fn split_dotted(path: &str) -> Result<Vec<String>> {
+    let mut out = vec![];
+    let mut rest = path;
+    loop {
+        let (token, tail) = match rest.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted_end(quoted).ok_or_else(|| invalid_path(path, "unterminated quote"))?;
+                (unescape_all(&quoted[..end])?, &quoted[end + 1..])
+            }
+            None => {
+                let end = rest.find('.').unwrap_or(rest.len());
+                (rest[..end].to_string(), &rest[end..])
+            }
+        };
+        out.push(token);
+        match tail.strip_prefix('.') {
+            Some(tail) => rest = tail,
+            None if tail.is_empty() => return Ok(out),
+            None => return Err(invalid_path(path, "expect `.` after a quoted key")),
+        }
+    }
+}
+
+/// The position of the closing quote, skipping escaped chars.
+pub(super) fn quoted_end(s: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i),
+            _ => (),
+        }
+    }
+    None
+}
+
+fn invalid_path(path: &str, reason: &str) -> RuntimeError {
+    RuntimeError::structure_error(ErrorCode::InvalidKey, format!("invalid path `{}`: {}", path, reason))
+}
+
+/// The index of a token under a list, negative indices count from the end.
+fn list_index(token: &str) -> Result<Integer> {
+    match isize::from_str(token) {
+        Ok(o) => Ok(Integer::from(o)),
+        Err(_) => Err(RuntimeError::structure_error(ErrorCode::InvalidKey, format!("`{}` is not a list index", token))),
+    }
+}
+
+impl Value {
+    /// Get the value at a dotted path or JSON pointer, see [`parse_path`].
+    pub fn pointer(&self, path: &str) -> Option<&Value> {
+        let mut target = self;
+        for token in parse_path(path).ok()? {
+            target = match target {
+                Value::Dict(dict) => dict.get(&token)?,
+                Value::List(list) => list.get(&token)?,
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+    pub fn pointer_mut(&mut self, path: &str) -> Option<&mut Value> {
+        let mut target = self;
+        for token in parse_path(path).ok()? {
+            target = match target {
+                Value::Dict(dict) => dict.get_mut(&token)?,
+                Value::List(list) => list.get_mut(&token)?,
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+    /// Set the value at a path, missing dicts and lists on the way are created.
+    ///
+    /// Under null a numeric segment creates a list, any other segment creates a dict.
+    /// `-` under a list appends a new item as in RFC 6902.
+    pub fn set_path(&mut self, path: &str, value: impl Into<Value>) -> Result<()> {
+        let mut target = self;
+        for token in parse_path(path)? {
+            let index = match &*target {
+                Value::List(list) if token == "-" => Some(Integer::from(list.next_index())),
+                Value::List(_) => Some(list_index(&token)?),
+                Value::Null if token == "-" => Some(Integer::from(0)),
+                Value::Null => isize::from_str(&token).ok().map(Integer::from),
+                _ => None,
+            };
+            target = match index {
+                Some(index) => target.ensure_index(index)?,
+                None => target.ensure_key(Text::from(token))?,
+            };
+        }
+        *target = value.into();
+        Ok(())
+    }
+    /// Remove the value at a path and return it, later items of a list are shifted down.
+    pub fn remove_path(&mut self, path: &str) -> Option<Value> {
+        let mut tokens = parse_path(path).ok()?;
+        let last = tokens.pop()?;
+        let mut parent = self;
+        for token in tokens {
+            parent = match parent {
+                Value::Dict(dict) => dict.get_mut(&token)?,
+                Value::List(list) => list.get_mut(&token)?,
+                _ => return None,
+            };
+        }
+        match parent {
+            Value::Dict(dict) => dict.remove(&last),
+            Value::List(list) => list.remove(&last),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn test_path() {
+    use crate::{dict, list};
+    assert_eq!(parse_path("a.\"b.c\".0").unwrap(), vec!["a", "b.c", "0"]);
+    assert_eq!(parse_path("/a/b~1c/~0/").unwrap(), vec!["a", "b/c", "~", ""]);
+    assert!(parse_path("a.\"b").is_err());
+    assert!(parse_path("/a~2").is_err());
+    let mut data = dict! {
+        "x": dict! {
+            "y": list!["z", "zz"],
+            "a.b": 1,
+            "c/d": 2
+        }
+    };
+    assert_eq!(data.pointer("x.y.0"), Some(&Value::from("z")));
+    assert_eq!(data.pointer("/x/y/-1"), Some(&Value::from("zz")));
+    assert_eq!(data.pointer("x.\"a.b\""), Some(&Value::from(1)));
+    assert_eq!(data.pointer("/x/a.b"), Some(&Value::from(1)));
+    assert_eq!(data.pointer("/x/c~1d"), Some(&Value::from(2)));
+    assert_eq!(data.pointer("x.c/d"), Some(&Value::from(2)));
+    *data.pointer_mut("x.y.1").unwrap() = Value::from("w");
+    assert_eq!(data.pointer("x.y.1"), Some(&Value::from("w")));
+    data.set_path("x.y.-", 3).unwrap();
+    assert_eq!(data.pointer("x.y.2"), Some(&Value::from(3)));
+    data.set_path("/new/list/0/key", true).unwrap();
+    assert_eq!(data.pointer("new.list.0.key"), Some(&Value::from(true)));
+    assert!(data.set_path("x.c/d.e", 1).is_err());
+    assert_eq!(data.remove_path("x.y.0"), Some(Value::from("z")));
+    assert_eq!(data.pointer("x.y"), Some(&list!["w", 3]));
+    assert_eq!(data.remove_path("/x/a.b"), Some(Value::from(1)));
+    assert_eq!(data.remove_path("x.missing"), None);
+    assert_eq!(data.pointer("x"), Some(&dict! {"y": list!["w", 3], "c/d": 2}));
+}
@@ -0,0 +1,574 @@
+use super::{
+    path::{quoted_end, unescape_pointer},
+    *,
+};
+use num::ToPrimitive;
+use std::cmp::Ordering;
+
+/// A compiled path query, see [`Value::query`] for the syntax.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+/// A value found by a query and its concrete path, negative indices are resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryMatch<'a> {
+    pub path: Vec<String>,
+    pub value: &'a Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    /// A key under a dict or an index under a list
+    Child(String),
+    /// `*`, every child
+    Wildcard,
+    /// `**`, the value itself and all descendants
+    Descend,
+    /// `[start:end:step]`
+    Slice(Option<isize>, Option<isize>, isize),
+    /// `[?filter]`, every child that passes the filter
+    Filter(Filter),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Or(Box<Filter>, Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    /// The operand exists and is neither null nor false
+    Truthy(Operand),
+    Compare(Operand, Compare, Operand),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    /// `@.a.b` or `a.b`, relative to the child under test
+    Path(Vec<String>),
+    Literal(Value),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Compare {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Value {
+    /// Find all values that match a query, in document order.
+    ///
+    /// - `servers.alpha.ip` and `servers."a.b"`, keys as in [`parse_path`], indices may be negative
+    /// - `servers.*.pw` or `servers[*]`, every child of a dict or list
+    /// - `**.port`, the port of the value and of all its descendants
+    /// - `ports[1:]`, `ports[::-1]`, slices of a list as in python
+    /// - `servers[?enabled == true].host`, children that pass a filter, `@` is the child itself
+    /// - `/servers/*/pw`, a JSON pointer where `*` and `**` work as above
+    ///
+    /// A key can not be empty, `a..b` is an error.
+    ///
+    /// A filter compares with `==`, `!=`, `<`, `<=`, `>`, `>=` and joins with `&&`, `||`, `!` and parentheses.
+    /// A path alone passes if it exists and is neither null nor false.
+    pub fn query(&self, query: &str) -> Result<Vec<QueryMatch<'_>>> {
+        Ok(Query::from_str(query)?.find(self))
+    }
+}
+
+impl FromStr for Query {
+    type Err = RuntimeError;
+
+    fn from_str(query: &str) -> Result<Self> {
+        if let Some(pointer) = query.strip_prefix('/') {
+            let mut steps = vec![];
+            for token in pointer.split('/') {
+                steps.push(match token {
+                    "*" => Step::Wildcard,
+                    "**" => Step::Descend,
+                    _ => Step::Child(unescape_pointer(query, token)?),
+                })
+            }
+            return Ok(Query { steps });
+        }
+        let mut parser = QueryParser { query, rest: query.strip_prefix('$').unwrap_or(query) };
+        let steps = parser.parse_steps()?;
+        Ok(Query { steps })
+    }
+}
+
+impl Query {
+    pub fn find<'a>(&self, value: &'a Value) -> Vec<QueryMatch<'a>> {
+        let mut current = vec![QueryMatch { path: vec![], value }];
+        for step in &self.steps {
+            let mut next = vec![];
+            for item in current {
+                step.apply(item, &mut next)
+            }
+            current = next;
+        }
+        current
+    }
+    /// The first key if the query starts with one.
+    pub fn first_key(&self) -> Option<&str> {
+        match self.steps.first() {
+            Some(Step::Child(key)) => Some(key),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> QueryMatch<'a> {
+    fn child(&self, token: String, value: &'a Value) -> Self {
+        let mut path = self.path.clone();
+        path.push(token);
+        QueryMatch { path, value }
+    }
+    fn children(&self, out: &mut Vec<QueryMatch<'a>>) {
+        match self.value {
+            Value::Dict(dict) => out.extend(dict.iter().map(|(k, v)| self.child(k.to_string(), v))),
+            Value::List(list) => out.extend(list.iter().map(|(i, v)| self.child(i.to_string(), v))),
+            _ => (),
+        }
+    }
+}
+
+impl Step {
+    fn apply<'a>(&self, item: QueryMatch<'a>, out: &mut Vec<QueryMatch<'a>>) {
+        match self {
+            Step::Child(token) => {
+                if let Some((token, value)) = get_child(item.value, token) {
+                    out.push(item.child(token, value))
+                }
+            }
+            Step::Wildcard => item.children(out),
+            Step::Descend => {
+                let mut stack = vec![item];
+                while let Some(item) = stack.pop() {
+                    let mut children = vec![];
+                    item.children(&mut children);
+                    out.push(item);
+                    stack.extend(children.into_iter().rev());
+                }
+            }
+            Step::Slice(start, end, step) => {
+                if let Value::List(list) = item.value {
+                    for index in slice_indices(list.length(), *start, *end, *step) {
+                        if let Some(value) = list.get(&index.to_string()) {
+                            out.push(item.child(index.to_string(), value))
+                        }
+                    }
+                }
+            }
+            Step::Filter(filter) => {
+                let mut children = vec![];
+                item.children(&mut children);
+                out.extend(children.into_iter().filter(|child| filter.test(child.value)))
+            }
+        }
+    }
+}
+
+/// A key under a dict or an index under a list, with the concrete token.
+fn get_child<'a>(value: &'a Value, token: &str) -> Option<(String, &'a Value)> {
+    match value {
+        Value::Dict(dict) => Some((token.to_string(), dict.get(token)?)),
+        Value::List(list) => {
+            let index = list.resolve_index(isize::from_str(token).ok()?)?;
+            Some((index.to_string(), list.get(&index.to_string())?))
+        }
+        _ => None,
+    }
+}
+
+fn slice_indices(length: usize, start: Option<isize>, end: Option<isize>, step: isize) -> Vec<usize> {
+    let length = length as isize;
+    let clamp = |i: isize, low: isize, high: isize| {
+        let i = if i < 0 { i + length } else { i };
+        i.max(low).min(high)
+    };
+    let mut out = vec![];
+    match step > 0 {
+        true => {
+            let mut i = clamp(start.unwrap_or(0), 0, length);
+            let end = clamp(end.unwrap_or(length), 0, length);
+            while i < end {
+                out.push(i as usize);
+                match i.checked_add(step) {
+                    Some(n) => i = n,
+                    None => break,
+                }
+            }
+        }
+        false => {
+            let mut i = clamp(start.unwrap_or(length - 1), -1, length - 1);
+            let end = end.map(|e| clamp(e, -1, length - 1)).unwrap_or(-1);
+            while i > end {
+                out.push(i as usize);
+                match i.checked_add(step) {
+                    Some(n) => i = n,
+                    None => break,
+                }
+            }
+        }
+    }
+    out
+}
+
+impl Filter {
+    fn test(&self, item: &Value) -> bool {
+        match self {
+            Filter::Or(lhs, rhs) => lhs.test(item) || rhs.test(item),
+            Filter::And(lhs, rhs) => lhs.test(item) && rhs.test(item),
+            Filter::Not(inner) => !inner.test(item),
+            Filter::Truthy(operand) => !matches!(operand.resolve(item), None | Some(Value::Null) | Some(Value::Boolean(false))),
+            Filter::Compare(lhs, op, rhs) => {
+                let (lhs, rhs) = match (lhs.resolve(item), rhs.resolve(item)) {
+                    (Some(lhs), Some(rhs)) => (lhs, rhs),
+                    _ => return false,
+                };
+                match op {
+                    Compare::Eq => is_equal(lhs, rhs),
+                    Compare::Ne => !is_equal(lhs, rhs),
+                    Compare::Lt => compare(lhs, rhs) == Some(Ordering::Less),
+                    Compare::Le => matches!(compare(lhs, rhs), Some(Ordering::Less) | Some(Ordering::Equal)),
+                    Compare::Gt => compare(lhs, rhs) == Some(Ordering::Greater),
+                    Compare::Ge => matches!(compare(lhs, rhs), Some(Ordering::Greater) | Some(Ordering::Equal)),
+                }
+            }
+        }
+    }
+}
+
+impl Operand {
+    fn resolve<'a>(&'a self, item: &'a Value) -> Option<&'a Value> {
+        match self {
+            Operand::Literal(value) => Some(value),
+            Operand::Path(path) => {
+                let mut out = item;
+                for token in path {
+                    out = get_child(out, token)?.1;
+                }
+                Some(out)
+            }
+        }
+    }
+}
+
+/// Strings by text, numbers by value and other values by kind and content.
+fn is_equal(lhs: &Value, rhs: &Value) -> bool {
+    match compare(lhs, rhs) {
+        Some(o) => o == Ordering::Equal,
+        None => lhs == rhs,
+    }
+}
+
+fn compare(lhs: &Value, rhs: &Value) -> Option<Ordering> {
+    match (lhs, rhs) {
+        (Value::String(lhs), Value::String(rhs)) => Some(lhs.as_str().cmp(rhs.as_str())),
+        (Value::Boolean(lhs), Value::Boolean(rhs)) => Some(lhs.cmp(rhs)),
+        (Value::DateTime(lhs), Value::DateTime(rhs)) if lhs == rhs => Some(Ordering::Equal),
+        // exact unless one side is an infinity or nan
+        _ => match (as_exact(lhs), as_exact(rhs)) {
+            (Some(lhs), Some(rhs)) => Some(lhs.cmp(&rhs)),
+            _ => as_float(lhs)?.partial_cmp(&as_float(rhs)?),
+        },
+    }
+}
+
+fn as_exact(value: &Value) -> Option<BigDecimal> {
+    match value {
+        Value::Integer(v) => Some(BigDecimal::from(v.get_value())),
+        Value::Decimal(v) if v.get_special().is_none() => Some(v.get_value()),
+        _ => None,
+    }
+}
+
+fn as_float(value: &Value) -> Option<f64> {
+    match value {
+        Value::Integer(v) => v.get_value().to_f64(),
+        Value::Decimal(v) => Some(v.to_f64()),
+        _ => None,
+    }
+}
+
+struct QueryParser<'a> {
+    query: &'a str,
+    rest: &'a str,
+}
+
+impl<'a> QueryParser<'a> {
+    fn error(&self, reason: &str) -> RuntimeError {
+        let at = self.query.len() - self.rest.len();
+        RuntimeError::structure_error(ErrorCode::InvalidKey, format!("invalid query `{}` at {}: {}", self.query, at, reason))
+    }
+    fn peek(&self) -> Option<char> {
+        self.rest.chars().next()
+    }
+    fn eat(&mut self, prefix: &str) -> bool {
+        match self.rest.strip_prefix(prefix) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+    fn skip_space(&mut self) {
+        self.rest = self.rest.trim_start()
+    }
+    fn expect(&mut self, prefix: &str) -> Result<()> {
+        self.skip_space();
+        match self.eat(prefix) {
+            true => Ok(()),
+            false => Err(self.error(&format!("expect `{}`", prefix))),
+        }
+    }
+
+    fn parse_steps(&mut self) -> Result<Vec<Step>> {
+        let mut steps = vec![];
+        // the first key needs no dot
+        if !matches!(self.peek(), None | Some('.') | Some('[')) {
+            steps.push(self.parse_segment()?);
+        }
+        while let Some(c) = self.peek() {
+            match c {
+                '.' => {
+                    self.eat(".");
+                    steps.push(self.parse_segment()?)
+                }
+                '[' => {
+                    self.eat("[");
+                    steps.push(self.parse_bracket()?)
+                }
+                _ => return Err(self.error("expect `.` or `[`")),
+            }
+        }
+        Ok(steps)
+    }
+
+    /// `**`, `*`, a quoted key, or a key up to the next `.` or `[`
+    fn parse_segment(&mut self) -> Result<Step> {
+        if self.eat("**") {
+            return Ok(Step::Descend);
+        }
+        if self.eat("*") {
+            return Ok(Step::Wildcard);
+        }
+        match self.peek() {
+            Some('"') | Some('\'') => Ok(Step::Child(self.parse_quoted()?)),
+            _ => Ok(Step::Child(self.parse_key(|c| !matches!(c, '.' | '[' | ']'))?)),
+        }
+    }
+
+    fn parse_key(&mut self, accept: impl Fn(char) -> bool) -> Result<String> {
+        let end = self.rest.find(|c: char| !accept(c)).unwrap_or(self.rest.len());
+        if end == 0 {
+            return Err(self.error("expect a key"));
+        }
+        let (key, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Ok(key.to_string())
+    }
+
+    fn parse_quoted(&mut self) -> Result<String> {
+        if self.eat("'") {
+            let end = self.rest.find('\'').ok_or_else(|| self.error("unterminated quote"))?;
+            let out = self.rest[..end].to_string();
+            self.rest = &self.rest[end + 1..];
+            return Ok(out);
+        }
+        self.eat("\"");
+        let end = quoted_end(self.rest).ok_or_else(|| self.error("unterminated quote"))?;
+        let out = unescape_all(&self.rest[..end])?;
+        self.rest = &self.rest[end + 1..];
+        Ok(out)
+    }
+
+    /// After `[`, a wildcard, key, index, slice or filter up to `]`
+    fn parse_bracket(&mut self) -> Result<Step> {
+        self.skip_space();
+        let step = match self.peek() {
+            Some('*') => {
+                self.eat("*");
+                Step::Wildcard
+            }
+            Some('?') => {
+                self.eat("?");
+                Step::Filter(self.parse_or()?)
+            }
+            Some('"') | Some('\'') => Step::Child(self.parse_quoted()?),
+            _ => {
+                let start = self.parse_integer()?;
+                self.skip_space();
+                match self.eat(":") {
+                    false => Step::Child(start.ok_or_else(|| self.error("expect an index"))?.to_string()),
+                    true => {
+                        let end = self.parse_integer()?;
+                        self.skip_space();
+                        let step = match self.eat(":") {
+                            true => self.parse_integer()?.unwrap_or(1),
+                            false => 1,
+                        };
+                        if step == 0 {
+                            return Err(self.error("slice step can not be zero"));
+                        }
+                        Step::Slice(start, end, step)
+                    }
+                }
+            }
+        };
+        self.expect("]")?;
+        Ok(step)
+    }
+
+    fn parse_integer(&mut self) -> Result<Option<isize>> {
+        self.skip_space();
+        let end = self.rest.char_indices().find(|(i, c)| !(c.is_ascii_digit() || (*i == 0 && *c == '-'))).map(|(i, _)| i).unwrap_or(self.rest.len());
+        if end == 0 {
+            return Ok(None);
+        }
+        let n = isize::from_str(&self.rest[..end]).map_err(|_| self.error("expect an integer"))?;
+        self.rest = &self.rest[end..];
+        Ok(Some(n))
+    }
+
+    fn parse_or(&mut self) -> Result<Filter> {
+        let mut out = self.parse_and()?;
+        loop {
+            self.skip_space();
+            match self.eat("||") {
+                true => out = Filter::Or(Box::new(out), Box::new(self.parse_and()?)),
+                false => return Ok(out),
+            }
+        }
+    }
+
+    fn parse_and(&mut self) -> Result<Filter> {
+        let mut out = self.parse_unary()?;
+        loop {
+            self.skip_space();
+            match self.eat("&&") {
+                true => out = Filter::And(Box::new(out), Box::new(self.parse_unary()?)),
+                false => return Ok(out),
+            }
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Filter> {
+        self.skip_space();
+        if self.rest.starts_with('!') && !self.rest.starts_with("!=") {
+            self.eat("!");
+            return Ok(Filter::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.eat("(") {
+            let out = self.parse_or()?;
+            self.expect(")")?;
+            return Ok(out);
+        }
+        let lhs = self.parse_operand()?;
+        self.skip_space();
+        let op = match () {
+            _ if self.eat("==") => Compare::Eq,
+            _ if self.eat("!=") => Compare::Ne,
+            _ if self.eat("<=") => Compare::Le,
+            _ if self.eat(">=") => Compare::Ge,
+            _ if self.eat("<") => Compare::Lt,
+            _ if self.eat(">") => Compare::Gt,
+            _ => return Ok(Filter::Truthy(lhs)),
+        };
+        Ok(Filter::Compare(lhs, op, self.parse_operand()?))
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand> {
+        self.skip_space();
+        let is_key = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+        let mut path = vec![];
+        match self.peek() {
+            Some('"') | Some('\'') => return Ok(Operand::Literal(Value::from(self.parse_quoted()?))),
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' => {
+                let end = self.rest.find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '+' | '-'))).unwrap_or(self.rest.len());
+                let value = parse_number(&self.rest[..end]).ok_or_else(|| self.error("expect a number"))?;
+                self.rest = &self.rest[end..];
+                return Ok(Operand::Literal(value));
+            }
+            Some('@') => {
+                self.eat("@");
+            }
+            _ => {
+                let key = self.parse_key(is_key)?;
+                match key.as_str() {
+                    "true" => return Ok(Operand::Literal(Value::from(true))),
+                    "false" => return Ok(Operand::Literal(Value::from(false))),
+                    "null" => return Ok(Operand::Literal(Value::Null)),
+                    _ => path.push(key),
+                }
+            }
+        }
+        loop {
+            match self.peek() {
+                Some('.') => {
+                    self.eat(".");
+                    path.push(self.parse_key(is_key)?)
+                }
+                Some('[') => {
+                    self.eat("[");
+                    self.skip_space();
+                    let token = match self.peek() {
+                        Some('"') | Some('\'') => self.parse_quoted()?,
+                        _ => self.parse_integer()?.ok_or_else(|| self.error("expect an index or a quoted key"))?.to_string(),
+                    };
+                    self.expect("]")?;
+                    path.push(token)
+                }
+                _ => return Ok(Operand::Path(path)),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_query() {
+    use crate::{dict, list};
+    let data = dict! {
+        "servers": dict! {
+            "alpha": dict! {"ip": "10.0.0.1", "pw": "a", "enabled": true, "port": 8001},
+            "beta": dict! {"ip": "10.0.0.2", "pw": "b", "enabled": false, "port": 8002},
+            "gamma": dict! {"ip": "10.0.0.3", "enabled": true, "port": 9000}
+        },
+        "ports": list![1, 2, 3, 4, 5],
+        "nested": list![dict! {"port": 1}, list![dict! {"port": 2}]]
+    };
+    let values = |query: &str| data.query(query).unwrap().into_iter().map(|m| m.value.clone()).collect::<Vec<_>>();
+    let paths = |query: &str| data.query(query).unwrap().into_iter().map(|m| m.path.join(".")).collect::<Vec<_>>();
+    assert_eq!(values("servers.*.pw"), vec![Value::from("a"), Value::from("b")]);
+    assert_eq!(values("/servers/*/pw"), values("$.servers[*].pw"));
+    assert_eq!(paths("servers[?enabled == true].host"), Vec::<String>::new());
+    assert_eq!(paths("servers[?enabled == true].ip"), vec!["servers.alpha.ip", "servers.gamma.ip"]);
+    assert_eq!(paths("servers[?port >= 8002 && !(enabled)]"), vec!["servers.beta"]);
+    assert_eq!(paths("servers[?@.pw]"), vec!["servers.alpha", "servers.beta"]);
+    assert_eq!(paths("servers[?ip == '10.0.0.3' || pw == \"a\"].port"), vec!["servers.alpha.port", "servers.gamma.port"]);
+    assert_eq!(paths("ports[-1]"), vec!["ports.4"]);
+    assert_eq!(values("ports[1:3]"), vec![Value::from(2), Value::from(3)]);
+    assert_eq!(values("ports[::-2]"), vec![Value::from(5), Value::from(3), Value::from(1)]);
+    assert_eq!(values("ports[-2:]"), vec![Value::from(4), Value::from(5)]);
+    assert_eq!(values("ports[?@ > 3.5]"), vec![Value::from(4), Value::from(5)]);
+    assert_eq!(paths("nested.**.port"), vec!["nested.0.port", "nested.1.0.port"]);
+    assert_eq!(paths("**.alpha.port"), vec!["servers.alpha.port"]);
+    assert_eq!(values("/nested/**/port"), values("nested.**.port"));
+    assert_eq!(paths("\"servers\".beta.-1"), Vec::<String>::new());
+    assert!(data.query("ports[::0]").is_err());
+    assert_eq!(values("ports[1::9223372036854775807]"), vec![Value::from(2)]);
+    assert_eq!(values("ports[-1::-9223372036854775808]"), vec![Value::from(5)]);
+    assert!(data.query("servers[?port ==]").is_err());
+    assert!(data.query("servers.alpha]").is_err());
+    assert!(data.query("servers..port").is_err());
+    // numbers compare exactly
+    let ids = list![dict! {"id": 9007199254740992i64}, dict! {"id": 9007199254740993i64}, dict! {"id": 0.1}];
+    let found = |query: &str| ids.query(query).unwrap().into_iter().map(|m| m.path.join(".")).collect::<Vec<_>>();
+    assert_eq!(found("[?id == 9007199254740993]"), vec!["1"]);
+    assert_eq!(found("[?id > 9007199254740992]"), vec!["1"]);
+    assert_eq!(found("[?id == 0.1]"), vec!["2"]);
+}
@@ -0,0 +1,149 @@
+use super::*;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+/// A binary blob, written as `b64"..."` or `hex"..."` in arc.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Bytes {
+    value: Vec<u8>,
+}
+
+impl Display for Bytes {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "b64\"{}\"", self.to_base64())
+    }
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(value: Vec<u8>) -> Self {
+        Self { value }
+    }
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(value: &[u8]) -> Self {
+        Self { value: value.to_vec() }
+    }
+}
+
+impl From<Bytes> for Value {
+    fn from(v: Bytes) -> Self {
+        Value::Bytes(Box::new(v))
+    }
+}
+
+impl From<Bytes> for Vec<u8> {
+    fn from(v: Bytes) -> Self {
+        v.value
+    }
+}
+
+impl Bytes {
+    /// Decode standard or url-safe base64, whitespace is skipped and the padding is optional.
+    pub fn from_base64(s: &str) -> Result<Self> {
+        let mut value = Vec::with_capacity(s.len() * 3 / 4);
+        let (mut buffer, mut bits) = (0u32, 0);
+        let mut padding = false;
+        for c in s.bytes().filter(|c| !c.is_ascii_whitespace()) {
+            let n = match c {
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'+' | b'-' => 62,
+                b'/' | b'_' => 63,
+                b'=' => {
+                    padding = true;
+                    continue;
+                }
+                _ => return Err(invalid_bytes("base64", s)),
+            };
+            if padding {
+                return Err(invalid_bytes("base64", s));
+            }
+            buffer = buffer << 6 | u32::from(n);
+            bits += 6;
+            if bits >= 8 {
+                bits -= 8;
+                value.push((buffer >> bits) as u8);
+            }
+        }
+        // a single char left over can not encode a whole byte
+        match bits {
+            6 => Err(invalid_bytes("base64", s)),
+            _ => Ok(Self { value }),
+        }
+    }
+    /// Decode hex digits in either case, whitespace is skipped.
+    pub fn from_hex(s: &str) -> Result<Self> {
+        let digits: Vec<u8> = s.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+        if !digits.len().is_multiple_of(2) {
+            return Err(invalid_bytes("hex", s));
+        }
+        let mut value = Vec::with_capacity(digits.len() / 2);
+        for pair in digits.chunks(2) {
+            let high = (pair[0] as char).to_digit(16).ok_or_else(|| invalid_bytes("hex", s))?;
+            let low = (pair[1] as char).to_digit(16).ok_or_else(|| invalid_bytes("hex", s))?;
+            value.push((high << 4 | low) as u8)
+        }
+        Ok(Self { value })
+    }
+    /// Standard base64 with padding.
+    pub fn to_base64(&self) -> String {
+        let mut out = String::with_capacity(self.value.len().div_ceil(3) * 4);
+        for chunk in self.value.chunks(3) {
+            let n = chunk.iter().fold(0u32, |acc, &b| acc << 8 | u32::from(b)) << (8 * (3 - chunk.len()));
+            for i in 0..4 {
+                match i <= chunk.len() {
+                    true => out.push(BASE64[(n >> (18 - 6 * i) & 63) as usize] as char),
+                    false => out.push('='),
+                }
+            }
+        }
+        out
+    }
+    /// Lowercase hex digits.
+    pub fn to_hex(&self) -> String {
+        let mut out = String::with_capacity(self.value.len() * 2);
+        for b in &self.value {
+            out.push(HEX[(b >> 4) as usize] as char);
+            out.push(HEX[(b & 15) as usize] as char);
+        }
+        out
+    }
+    pub fn get_value(&self) -> Vec<u8> {
+        self.value.to_owned()
+    }
+}
+
+fn invalid_bytes(encoding: &str, s: &str) -> RuntimeError {
+    RuntimeError::syntax_error(ErrorCode::HandlerFailed, format!("invalid {} literal `{}`", encoding, s), None)
+}
+
+#[test]
+fn test_bytes() {
+    for s in &["", "f", "fo", "foo", "foob", "fooba", "foobar"] {
+        let bytes = Bytes::from(s.as_bytes());
+        assert_eq!(Bytes::from_base64(&bytes.to_base64()).unwrap(), bytes);
+        assert_eq!(Bytes::from_hex(&bytes.to_hex()).unwrap(), bytes);
+    }
+    assert_eq!(Bytes::from(&b"foobar"[..]).to_base64(), "Zm9vYmFy");
+    assert_eq!(Bytes::from(&b"fo"[..]).to_base64(), "Zm8=");
+    assert_eq!(&*Bytes::from_base64("Zm8").unwrap(), b"fo");
+    assert_eq!(&*Bytes::from_base64("Zm9v\n YmFy").unwrap(), b"foobar");
+    assert_eq!(&*Bytes::from_base64("-_8=").unwrap(), &[0xfb, 0xff]);
+    assert_eq!(&*Bytes::from_hex("DE ad be EF").unwrap(), &[0xde, 0xad, 0xbe, 0xef]);
+    assert!(Bytes::from_base64("Zm9vY").is_err());
+    assert!(Bytes::from_base64("Zm=8").is_err());
+    assert!(Bytes::from_base64("Zm9v!").is_err());
+    assert!(Bytes::from_hex("abc").is_err());
+    assert!(Bytes::from_hex("zz").is_err());
+}
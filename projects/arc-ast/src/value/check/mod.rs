@@ -70,10 +70,22 @@ impl Value {
             _ => false,
         }
     }
+    pub fn is_bytes(&self) -> bool {
+        match self {
+            Value::Bytes(_) => true,
+            _ => false,
+        }
+    }
+    pub fn is_datetime(&self) -> bool {
+        match self {
+            Value::DateTime(_) => true,
+            _ => false,
+        }
+    }
     pub fn is_zero(&self) -> bool {
         match self {
             Value::Integer(n) => n.is_zero(),
-            Value::Decimal(n) => n.is_zero(),
+            Value::Decimal(n) => n.is_finite() && n.is_zero(),
             _ => false,
         }
     }
@@ -89,7 +101,7 @@ impl Value {
     }
     pub fn get_handler(&self) -> Option<String> {
         match self {
-            Value::Null | Value::Boolean(_) => None,
+            Value::Null | Value::Boolean(_) | Value::DateTime(_) | Value::Bytes(_) => None,
             Value::Integer(v) => v.get_handler(),
             Value::Decimal(v) => v.get_handler(),
             Value::String(v) => v.get_handler(),
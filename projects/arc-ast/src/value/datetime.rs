@@ -0,0 +1,276 @@
+use super::*;
+
+/// A RFC 3339 date, time, local datetime or offset datetime, as found in TOML and YAML.
+///
+/// At least one of `date` and `time` is set, `offset` is only set when both are.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct DateTime {
+    date: Option<Date>,
+    time: Option<Time>,
+    offset: Option<Offset>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Date {
+    pub year: u16,
+    /// 1 to 12
+    pub month: u8,
+    /// 1 to 31
+    pub day: u8,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    /// 0 to 60, 60 is a leap second
+    pub second: u8,
+    /// Up to 9 fraction digits, more are rejected
+    pub nanosecond: u32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Offset {
+    /// `Z`, UTC
+    Z,
+    /// `-00:00`, UTC with an unknown local offset as in RFC 3339
+    Unknown,
+    /// `+08:00`, `-05:30`, minutes east of UTC
+    Custom { minutes: i16 },
+}
+
+impl Display for DateTime {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if let Some(date) = &self.date {
+            Display::fmt(date, f)?;
+        }
+        if let Some(time) = &self.time {
+            if self.date.is_some() {
+                write!(f, "T")?;
+            }
+            Display::fmt(time, f)?;
+        }
+        if let Some(offset) = &self.offset {
+            Display::fmt(offset, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl Display for Time {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)?;
+        if self.nanosecond != 0 {
+            let s = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", s.trim_end_matches('0'))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Offset {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Offset::Z => write!(f, "Z"),
+            Offset::Unknown => write!(f, "-00:00"),
+            Offset::Custom { minutes } => {
+                let sign = if minutes < 0 { '-' } else { '+' };
+                write!(f, "{}{:02}:{:02}", sign, minutes.abs() / 60, minutes.abs() % 60)
+            }
+        }
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = RuntimeError;
+
+    /// Accepts `1979-05-27`, `07:32:00`, `1979-05-27T07:32:00` and `1979-05-27T07:32:00.999-07:00`,
+    /// the separator can also be `t` or a space as in TOML.
+    fn from_str(s: &str) -> Result<Self> {
+        let error = || RuntimeError::syntax_error(ErrorCode::InvalidDateTime, format!("invalid datetime `{}`", s), None);
+        let mut rest = s.as_bytes();
+        let mut out = DateTime { date: None, time: None, offset: None };
+        if rest.len() >= 10 && rest[4] == b'-' {
+            out.date = Some(Date::parse(&rest[..10]).ok_or_else(error)?);
+            rest = &rest[10..];
+            match rest.first() {
+                None => return Ok(out),
+                Some(b'T') | Some(b't') | Some(b' ') => rest = &rest[1..],
+                Some(_) => return Err(error()),
+            }
+        }
+        let (time, tail) = Time::parse(rest).ok_or_else(error)?;
+        out.time = Some(time);
+        rest = tail;
+        if !rest.is_empty() {
+            if out.date.is_none() {
+                return Err(RuntimeError::syntax_error(
+                    ErrorCode::InvalidDateTime,
+                    format!("a time without date can not have an offset `{}`", s),
+                    None,
+                ));
+            }
+            out.offset = Some(Offset::parse(rest).ok_or_else(error)?);
+        }
+        Ok(out)
+    }
+}
+
+impl Date {
+    fn parse(s: &[u8]) -> Option<Self> {
+        if s.len() != 10 || s[4] != b'-' || s[7] != b'-' {
+            return None;
+        }
+        let date = Date { year: digits(&s[0..4])? as u16, month: digits(&s[5..7])? as u8, day: digits(&s[8..10])? as u8 };
+        match date.month {
+            1..=12 if date.day >= 1 && date.day <= days_in_month(date.year, date.month) => Some(date),
+            _ => None,
+        }
+    }
+}
+
+impl Time {
+    fn parse(s: &[u8]) -> Option<(Self, &[u8])> {
+        if s.len() < 8 || s[2] != b':' || s[5] != b':' {
+            return None;
+        }
+        let mut time = Time { hour: digits(&s[0..2])? as u8, minute: digits(&s[3..5])? as u8, second: digits(&s[6..8])? as u8, nanosecond: 0 };
+        let mut rest = &s[8..];
+        if rest.first() == Some(&b'.') {
+            let n = rest[1..].iter().take_while(|c| c.is_ascii_digit()).count();
+            if n == 0 || n > 9 {
+                return None;
+            }
+            let mut scale = 100_000_000;
+            for c in rest[1..].iter().take(n) {
+                time.nanosecond += scale * u32::from(c - b'0');
+                scale /= 10;
+            }
+            rest = &rest[n + 1..];
+        }
+        match time.hour < 24 && time.minute < 60 && time.second <= 60 {
+            true => Some((time, rest)),
+            false => None,
+        }
+    }
+}
+
+impl Offset {
+    fn parse(s: &[u8]) -> Option<Self> {
+        match s {
+            b"Z" | b"z" => Some(Offset::Z),
+            [sign @ b'+', h @ .., b':', m1, m2] | [sign @ b'-', h @ .., b':', m1, m2] if h.len() == 2 => {
+                let (hours, minutes) = (digits(h)?, digits(&[*m1, *m2])?);
+                if hours >= 24 || minutes >= 60 {
+                    return None;
+                }
+                let minutes = (hours * 60 + minutes) as i16;
+                if minutes == 0 && *sign == b'-' {
+                    return Some(Offset::Unknown);
+                }
+                Some(Offset::Custom { minutes: if *sign == b'-' { -minutes } else { minutes } })
+            }
+            _ => None,
+        }
+    }
+}
+
+fn digits(s: &[u8]) -> Option<u32> {
+    let mut out = 0;
+    for c in s {
+        match c.is_ascii_digit() {
+            true => out = out * 10 + u32::from(c - b'0'),
+            false => return None,
+        }
+    }
+    Some(out)
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl From<DateTime> for Value {
+    fn from(v: DateTime) -> Self {
+        Value::DateTime(Box::new(v))
+    }
+}
+
+impl From<Date> for DateTime {
+    fn from(date: Date) -> Self {
+        Self { date: Some(date), time: None, offset: None }
+    }
+}
+
+impl From<Time> for DateTime {
+    fn from(time: Time) -> Self {
+        Self { date: None, time: Some(time), offset: None }
+    }
+}
+
+impl DateTime {
+    /// A local datetime, or an offset datetime if `offset` is given.
+    pub fn new(date: Date, time: Time, offset: Option<Offset>) -> Self {
+        Self { date: Some(date), time: Some(time), offset }
+    }
+    pub fn get_date(&self) -> Option<Date> {
+        self.date
+    }
+    pub fn get_time(&self) -> Option<Time> {
+        self.time
+    }
+    pub fn get_offset(&self) -> Option<Offset> {
+        self.offset
+    }
+    /// Seconds since `1970-01-01T00:00:00Z`, only offset datetimes name an instant.
+    pub fn unix_timestamp(&self) -> Option<i64> {
+        let (date, time) = (self.date?, self.time?);
+        let offset = match self.offset? {
+            Offset::Z | Offset::Unknown => 0,
+            Offset::Custom { minutes } => i64::from(minutes) * 60,
+        };
+        // days from civil, http://howardhinnant.github.io/date_algorithms.html
+        let (y, m, d) = (i64::from(date.year), i64::from(date.month), i64::from(date.day));
+        let y = if m <= 2 { y - 1 } else { y };
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let doy = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146097 + doe - 719468;
+        let seconds = i64::from(time.hour) * 3600 + i64::from(time.minute) * 60 + i64::from(time.second);
+        Some(days * 86400 + seconds - offset)
+    }
+}
+
+#[test]
+fn test_datetime() {
+    let check = |s: &str, out: &str| assert_eq!(DateTime::from_str(s).unwrap().to_string(), out);
+    check("1979-05-27", "1979-05-27");
+    check("07:32:00.999900", "07:32:00.9999");
+    check("1979-05-27 07:32:00", "1979-05-27T07:32:00");
+    check("1979-05-27t07:32:00z", "1979-05-27T07:32:00Z");
+    check("1979-05-27T00:32:00.999999-07:00", "1979-05-27T00:32:00.999999-07:00");
+    check("2000-02-29T23:59:60+05:30", "2000-02-29T23:59:60+05:30");
+    check("1979-05-27T07:32:00-00:00", "1979-05-27T07:32:00-00:00");
+    check("1979-05-27T07:32:00+00:00", "1979-05-27T07:32:00+00:00");
+    assert!(DateTime::from_str("1900-02-29").is_err());
+    assert!(DateTime::from_str("1979-13-01").is_err());
+    assert!(DateTime::from_str("24:00:00").is_err());
+    assert!(DateTime::from_str("07:32:00Z").is_err());
+    assert!(DateTime::from_str("1979-05-27T07:32").is_err());
+    assert!(DateTime::from_str("1979-05-27T07:32:00+7:00").is_err());
+    assert!(DateTime::from_str("07:32:00.1234567891").is_err());
+    let t = DateTime::from_str("1979-05-27T07:32:00-07:00").unwrap();
+    assert_eq!(t.unix_timestamp(), Some(296_663_520));
+}
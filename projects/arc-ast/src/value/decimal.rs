@@ -1,14 +1,37 @@
 use super::*;
+use crate::value::integer::{is_integer_suffix, out_of_range};
+use num::ToPrimitive;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Decimal {
     handler: Option<String>,
     value: BigDecimal,
+    /// Set for floats that a `BigDecimal` can not hold, `value` is zero then
+    special: Option<IeeeSpecial>,
+}
+
+/// IEEE 754 floats without a decimal representation.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum IeeeSpecial {
+    /// `inf`, `+Infinity`
+    Infinity,
+    /// `-inf`, `-Infinity`
+    NegativeInfinity,
+    /// `nan`, `NaN`, the sign and payload are not kept
+    NaN,
+    /// `-0.0`
+    NegativeZero,
 }
 
 impl Display for Decimal {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "{}", self.value)?;
+        match self.special {
+            None => write!(f, "{}", self.value)?,
+            Some(IeeeSpecial::Infinity) => write!(f, "inf")?,
+            Some(IeeeSpecial::NegativeInfinity) => write!(f, "-inf")?,
+            Some(IeeeSpecial::NaN) => write!(f, "nan")?,
+            Some(IeeeSpecial::NegativeZero) => write!(f, "-0.0")?,
+        }
         match &self.handler {
             None => (),
             Some(s) => write!(f, "{}", s)?,
@@ -27,37 +50,44 @@ impl Deref for Decimal {
 
 impl From<f32> for Decimal {
     fn from(v: f32) -> Self {
-        Self { handler: None, value: BigDecimal::try_from(v).unwrap_or_default() }
+        Self::from(v as f64)
     }
 }
 
 impl From<f32> for Value {
     fn from(v: f32) -> Self {
-        match BigDecimal::try_from(v) {
-            Ok(v) => Value::from(v),
-            Err(_) => Value::Null,
-        }
+        Value::from(Decimal::from(v))
     }
 }
 
 impl From<f64> for Decimal {
     fn from(v: f64) -> Self {
-        Self { handler: None, value: BigDecimal::try_from(v).unwrap_or_default() }
+        let special = match v {
+            _ if v.is_nan() => IeeeSpecial::NaN,
+            _ if v == f64::INFINITY => IeeeSpecial::Infinity,
+            _ if v == f64::NEG_INFINITY => IeeeSpecial::NegativeInfinity,
+            _ if v == 0.0 && v.is_sign_negative() => IeeeSpecial::NegativeZero,
+            _ => return Self { handler: None, value: BigDecimal::try_from(v).unwrap_or_default(), special: None },
+        };
+        Self::from(special)
     }
 }
 
 impl From<f64> for Value {
     fn from(v: f64) -> Self {
-        match BigDecimal::try_from(v) {
-            Ok(v) => Value::from(v),
-            Err(_) => Value::Null,
-        }
+        Value::from(Decimal::from(v))
+    }
+}
+
+impl From<IeeeSpecial> for Decimal {
+    fn from(v: IeeeSpecial) -> Self {
+        Self { handler: None, value: BigDecimal::default(), special: Some(v) }
     }
 }
 
 impl From<BigDecimal> for Decimal {
     fn from(v: BigDecimal) -> Self {
-        Self { handler: None, value: v }
+        Self { handler: None, value: v, special: None }
     }
 }
 
@@ -80,4 +110,45 @@ impl Decimal {
     pub fn get_handler(&self) -> Option<String> {
         self.handler.to_owned()
     }
+    pub fn get_value(&self) -> BigDecimal {
+        self.value.to_owned()
+    }
+    pub fn get_special(&self) -> Option<IeeeSpecial> {
+        self.special
+    }
+    /// `false` for infinities and nan.
+    pub fn is_finite(&self) -> bool {
+        match self.special {
+            Some(IeeeSpecial::Infinity) | Some(IeeeSpecial::NegativeInfinity) | Some(IeeeSpecial::NaN) => false,
+            _ => true,
+        }
+    }
+    pub fn to_f64(&self) -> f64 {
+        match self.special {
+            None => self.value.to_f64().unwrap_or(f64::NAN),
+            Some(IeeeSpecial::Infinity) => f64::INFINITY,
+            Some(IeeeSpecial::NegativeInfinity) => f64::NEG_INFINITY,
+            Some(IeeeSpecial::NaN) => f64::NAN,
+            Some(IeeeSpecial::NegativeZero) => -0.0,
+        }
+    }
+    /// Check that the value fits the rust type named by the handler, other handlers are not checked.
+    ///
+    /// Integer suffixes are rejected, `1.5u8` is not a valid literal.
+    pub fn check_handler(&self) -> Result<()> {
+        let fits = match self.handler.as_deref() {
+            Some("f32") | Some("f64") if self.special.is_some() => true,
+            Some("f32") => self.value.to_f32().is_some_and(f32::is_finite),
+            Some("f64") => self.value.to_f64().is_some_and(f64::is_finite),
+            Some(h) if is_integer_suffix(h) => {
+                let message = format!("integer suffix on decimal `{}`", self);
+                return Err(RuntimeError::syntax_error(ErrorCode::InvalidNumber, message, None));
+            }
+            _ => true,
+        };
+        match fits {
+            true => Ok(()),
+            false => Err(out_of_range(self)),
+        }
+    }
 }
@@ -100,9 +100,15 @@ impl Dict {
     pub fn get_key(&self, key: &Text) -> Option<&Value> {
         self.value.get(&key.value)
     }
+    pub fn set_handler(&mut self, handler: impl Into<String>) {
+        self.handler = Some(handler.into())
+    }
     pub fn get_handler(&self) -> Option<String> {
         self.handler.to_owned()
     }
+    pub fn take_handler(&mut self) -> Option<String> {
+        self.handler.take()
+    }
 
     pub fn ensure_key(&mut self, key: Text) -> &'_ mut Value {
         self.entry(key.value).or_default()
@@ -128,6 +134,11 @@ impl Dict {
         self.value.insert(key, value)
     }
 
+    /// Remove a key and keep the order of the others.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.value.shift_remove(key)
+    }
+
     pub fn as_vec(&self) -> Vec<Value> {
         self.value.values().cloned().collect()
     }
@@ -1,9 +1,9 @@
 mod from_tuples;
 mod parse;
 
-pub use parse::parse_number;
+pub use parse::{is_type_suffix, parse_byte, parse_number};
 
-use crate::Value;
+use crate::{value::Integer, Value};
 use bigdecimal::BigDecimal;
 use num::{BigInt, Num};
 use std::str::FromStr;
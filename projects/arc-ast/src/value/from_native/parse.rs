@@ -1,4 +1,6 @@
 use super::*;
+use crate::value::integer::is_integer_suffix;
+use num::Zero;
 
 pub fn parse_number(s: &str) -> Option<Value> {
     let exp_separator: &[_] = &['e', 'E', '*'];
@@ -22,7 +24,7 @@ pub fn parse_number(s: &str) -> Option<Value> {
     let (digits, decimal_offset): (String, _) = match base_part.find('.') {
         None => {
             if exponent_value >= 0 {
-                return BigInt::from_str_radix(&base_part, 10).map(|i| i * 10_u32.pow(exponent_value as u32)).map(|i| Value::from(i)).ok();
+                return BigInt::from_str_radix(base_part, 10).map(|i| i * num::pow(BigInt::from(10), exponent_value as usize)).map(Value::from).ok();
             }
             else {
                 (base_part.to_string(), 0)
@@ -35,7 +37,49 @@ pub fn parse_number(s: &str) -> Option<Value> {
             (digits, trail.len() as i64)
         }
     };
-    BigInt::from_str_radix(&digits, 10).map(|big_int| BigDecimal::new(big_int, decimal_offset - exponent_value)).map(|i| Value::from(i)).ok()
+    let out = BigInt::from_str_radix(&digits, 10).map(|big_int| BigDecimal::new(big_int, decimal_offset - exponent_value)).ok()?;
+    // `-0.0` keeps its sign
+    match s.starts_with('-') && out.is_zero() {
+        true => Some(Value::from(-0.0)),
+        false => Some(Value::from(out)),
+    }
+}
+
+/// Decode `0x`, `0o` and `0b` literals, `_` separators are ignored.
+///
+/// The type suffix is kept as handler, it may be glued to the digits (`0b1u8`) or separated by `_` (`0x00_f32`).
+pub fn parse_byte(s: &str) -> Option<Integer> {
+    let radix = match s.get(0..2)? {
+        "0x" => 16,
+        "0o" => 8,
+        "0b" => 2,
+        _ => return None,
+    };
+    let body = &s[2..];
+    let split = body.find(|c: char| c != '_' && !c.is_digit(radix)).unwrap_or(body.len());
+    let (mut digits, mut handler) = body.split_at(split);
+    if handler.is_empty() {
+        if let Some(loc) = body.rfind('_') {
+            if is_type_suffix(&body[loc + 1..]) {
+                digits = &body[..loc];
+                handler = &body[loc + 1..];
+            }
+        }
+    }
+    if !handler.is_empty() && !handler.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let digits: String = digits.chars().filter(|c| *c != '_').collect();
+    let mut out = Integer::from(BigInt::from_str_radix(&digits, radix).ok()?);
+    if !handler.is_empty() {
+        out.set_handler(handler)
+    }
+    Some(out)
+}
+
+/// Whether a number suffix names a rust type, such as `u8` or `f64`.
+pub fn is_type_suffix(s: &str) -> bool {
+    is_integer_suffix(s) || s == "f32" || s == "f64"
 }
 
 #[test]
@@ -48,3 +92,14 @@ fn test() {
     println!("{:?}", BigDecimal::from_str_radix("+0.21", 10).unwrap());
     println!("{:?}", BigDecimal::from_str_radix("+0.11", 10).unwrap());
 }
+
+#[test]
+fn test_byte() {
+    assert_eq!(parse_byte("0xFF").unwrap(), Integer::from(255));
+    assert_eq!(parse_byte("0o7_55").unwrap(), Integer::from(0o755));
+    assert_eq!(parse_byte("0b1010_1010").unwrap(), Integer::from(0b1010_1010));
+    assert_eq!(parse_byte("0x00_f32").unwrap().get_handler(), Some(String::from("f32")));
+    assert_eq!(parse_byte("0b11u8").unwrap().get_handler(), Some(String::from("u8")));
+    assert!(parse_byte("0b12").is_none());
+    assert!(parse_byte("0x").is_none());
+}
@@ -1,4 +1,5 @@
 use super::*;
+use num::ToPrimitive;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Integer {
@@ -78,4 +79,37 @@ impl Integer {
     pub fn get_index(&self) -> Option<isize> {
         isize::try_from(&self.value).ok()
     }
+    /// Check that the value fits the rust type named by the handler, other handlers are not checked.
+    pub fn check_handler(&self) -> Result<()> {
+        let v = &self.value;
+        let fits = match self.handler.as_deref() {
+            Some("i8") => i8::try_from(v).is_ok(),
+            Some("i16") => i16::try_from(v).is_ok(),
+            Some("i32") => i32::try_from(v).is_ok(),
+            Some("i64") => i64::try_from(v).is_ok(),
+            Some("i128") => i128::try_from(v).is_ok(),
+            Some("isize") => isize::try_from(v).is_ok(),
+            Some("u8") => u8::try_from(v).is_ok(),
+            Some("u16") => u16::try_from(v).is_ok(),
+            Some("u32") => u32::try_from(v).is_ok(),
+            Some("u64") => u64::try_from(v).is_ok(),
+            Some("u128") => u128::try_from(v).is_ok(),
+            Some("usize") => usize::try_from(v).is_ok(),
+            Some("f32") => v.to_f32().is_some_and(f32::is_finite),
+            Some("f64") => v.to_f64().is_some_and(f64::is_finite),
+            _ => true,
+        };
+        match fits {
+            true => Ok(()),
+            false => Err(out_of_range(self)),
+        }
+    }
+}
+
+pub(crate) fn out_of_range(n: &impl Display) -> RuntimeError {
+    RuntimeError::syntax_error(ErrorCode::InvalidNumber, format!("number `{}` is out of range for its type", n), None)
+}
+
+pub(crate) fn is_integer_suffix(s: &str) -> bool {
+    matches!(s, "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize")
 }
@@ -1 +1,136 @@
+use crate::{
+    ast::{ASTKind, AST},
+    value::Text,
+    Value,
+};
 
+/// A dict without handler that can be written as `{scope}`, an empty scope would not create the dict.
+pub(crate) fn is_section(value: &Value) -> bool {
+    match value {
+        Value::Dict(v) => v.get_handler().is_none() && v.length() != 0,
+        _ => false,
+    }
+}
+
+/// A list that holds a dict or list is written as `[list]`, with `^` for sections and `>` for other items.
+pub(crate) fn is_list_scope(value: &Value) -> bool {
+    match value {
+        Value::List(v) => v.get_handler().is_none() && v.iter().any(|(_, v)| v.is_dict() || v.is_list()),
+        _ => false,
+    }
+}
+
+/// A dict becomes a program with `{scope}` and `[list]` heads in the layout of the pretty writer, any other value
+/// becomes a literal. Strings keep their delimiter and handlers are kept.
+impl From<Value> for AST {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Dict(_) => {
+                let mut program = vec![];
+                lower_scope(&value, 0, &mut program);
+                AST::program(program)
+            }
+            _ => lower(&value),
+        }
+    }
+}
+
+fn namespace(key: &str) -> AST {
+    AST::namespace(vec![AST::string(Text::from(key))])
+}
+
+/// The pairs of a dict followed by its sections, which are `depth` dots deep.
+fn lower_scope(value: &Value, depth: usize, out: &mut Vec<AST>) {
+    let dict = match value {
+        Value::Dict(v) => v,
+        _ => return,
+    };
+    for (key, value) in dict.iter().filter(|(_, v)| !is_section(v) && !is_list_scope(v)) {
+        out.push(AST::pair(namespace(key), lower(value)))
+    }
+    for (key, value) in dict.iter() {
+        match value {
+            Value::Dict(_) if is_section(value) => {
+                out.push(AST::from(ASTKind::DictScope(depth, Box::new(namespace(key)))));
+                lower_scope(value, depth + 1, out)
+            }
+            Value::List(list) if is_list_scope(value) => {
+                out.push(AST::from(ASTKind::ListScope(depth, Box::new(namespace(key)))));
+                for (holes, item) in list.runs() {
+                    out.extend((0..holes).map(|_| AST::from(ASTKind::ListAppend(Box::new(AST::from(ASTKind::Null))))));
+                    let kind = match item {
+                        Value::Dict(v) if is_section(item) => ASTKind::ListInsert(v.iter().map(|(k, v)| AST::pair(namespace(k), lower(v))).collect()),
+                        _ => ASTKind::ListAppend(Box::new(lower(item))),
+                    };
+                    out.push(AST::from(kind))
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// A value as literal, bytes have no literal and are kept as value.
+fn lower(value: &Value) -> AST {
+    let kind = match value {
+        Value::Null => ASTKind::Null,
+        Value::Boolean(v) => ASTKind::Boolean(*v),
+        Value::Integer(v) => ASTKind::Integer(v.clone()),
+        Value::Decimal(v) => ASTKind::Decimal(v.clone()),
+        Value::DateTime(v) => ASTKind::DateTime(v.clone()),
+        Value::Bytes(_) => ASTKind::Value(Box::new(value.clone())),
+        Value::String(v) => ASTKind::String(v.clone()),
+        Value::List(v) => {
+            let mut items = Vec::with_capacity(v.length());
+            for (holes, item) in v.runs() {
+                items.extend((0..holes).map(|_| AST::from(ASTKind::Null)));
+                items.push(lower(item))
+            }
+            let list = ASTKind::List(items);
+            match v.get_handler() {
+                Some(handler) => ASTKind::Handler(handler, Box::new(AST::from(list))),
+                None => list,
+            }
+        }
+        Value::Dict(v) => {
+            let dict = ASTKind::Dict(v.iter().map(|(k, v)| AST::pair(namespace(k), lower(v))).collect());
+            match v.get_handler() {
+                Some(handler) => ASTKind::Handler(handler, Box::new(AST::from(dict))),
+                None => dict,
+            }
+        }
+    };
+    AST::from(kind)
+}
+
+#[test]
+fn test_into_ast() {
+    use crate::{dict, list, value::Bytes};
+    use std::convert::TryFrom;
+    let mut point = dict! {"x": 1, "y": 2};
+    if let Value::Dict(v) = &mut point {
+        v.set_handler("vec")
+    }
+    let data = dict! {
+        "name": "arc",
+        "key": Bytes::from(&b"arc"[..]),
+        "empty": list![],
+        "server": dict! {
+            "host": "localhost",
+            "point": point,
+            "tls": dict! {"enabled": true},
+            "routes": list![dict! {"path": "/"}, dict! {"path": "/login", "methods": list!["get"]}, list![1]]
+        },
+        "other": dict! {"key": Value::Null, "none": dict! {}}
+    };
+    let ast = AST::from(data.clone());
+    match &ast.kind {
+        ASTKind::Program(v) => {
+            assert_eq!(v[0], AST::pair(namespace("name"), AST::string(Text::from("arc"))));
+            assert_eq!(v[3], AST::from(ASTKind::DictScope(0, Box::new(namespace("server")))));
+        }
+        _ => panic!("{:?}", ast),
+    }
+    assert_eq!(Value::try_from(ast).unwrap(), data);
+    assert_eq!(Value::try_from(AST::from(list![1, "x"])).unwrap(), list![1, "x"]);
+}
@@ -0,0 +1,46 @@
+use super::*;
+
+macro_rules! list2tuple {
+    ($n:expr; $($T:ident: $i:tt), +) => {
+    impl<$($T), +> FromValue for ($($T), +)
+    where
+        $($T: FromValue), +
+    {
+        fn from_value(value: &Value, path: &str) -> Result<Self> {
+            match value {
+                // a hole reads as null
+                Value::List(list) if list.length() == $n => {
+                    Ok(($($T::from_value(list.get(stringify!($i)).unwrap_or(&Value::Null), &child_path(path, stringify!($i)))?), +))
+                }
+                _ => Err(invalid_type(path, concat!("list of ", stringify!($n), " items"), value)),
+            }
+        }
+    }
+    impl<$($T), +> TryFrom<Value> for ($($T), +)
+    where
+        $($T: FromValue), +
+    {
+        type Error = RuntimeError;
+
+        fn try_from(value: Value) -> Result<Self> {
+            Self::from_value(&value, "")
+        }
+    }
+    impl<$($T), +> TryFrom<&Value> for ($($T), +)
+    where
+        $($T: FromValue), +
+    {
+        type Error = RuntimeError;
+
+        fn try_from(value: &Value) -> Result<Self> {
+            Self::from_value(value, "")
+        }
+    }
+    };
+}
+
+list2tuple!(2; T1: 0, T2: 1);
+list2tuple!(3; T1: 0, T2: 1, T3: 2);
+list2tuple!(4; T1: 0, T2: 1, T3: 2, T4: 3);
+list2tuple!(5; T1: 0, T2: 1, T3: 2, T4: 3, T5: 4);
+list2tuple!(6; T1: 0, T2: 1, T3: 2, T4: 3, T5: 4, T6: 5);
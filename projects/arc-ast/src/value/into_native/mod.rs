@@ -1,4 +1,18 @@
-use crate::{value::Text, Value};
+use crate::{
+    value::{Bytes, DateTime, Text},
+    ErrorCode, Result, RuntimeError, Value,
+};
+use indexmap::IndexMap;
+use num::ToPrimitive;
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    hash::{BuildHasher, Hash},
+    ops::Deref,
+    time::Duration,
+};
+
+mod into_tuples;
 
 impl From<Text> for String {
     fn from(v: Text) -> Self {
@@ -6,8 +20,323 @@ impl From<Text> for String {
     }
 }
 
-impl From<Value> for String {
-    fn from(_: Value) -> Self {
-        unimplemented!()
+/// Types that can be read out of a [`Value`], the conversion is strict and never parses strings.
+///
+/// `path` is where `value` was found, it is only used for error messages.
+pub trait FromValue: Sized {
+    fn from_value(value: &Value, path: &str) -> Result<Self>;
+}
+
+impl Value {
+    /// Read the value at a dotted path or JSON pointer.
+    ///
+    /// A missing value reads as `None` for an option, other types report the path as missing.
+    pub fn get_as<T: FromValue>(&self, path: &str) -> Result<T> {
+        match self.pointer(path) {
+            Some(value) => T::from_value(value, path),
+            None => T::from_value(&Value::Null, path).map_err(|_| RuntimeError::structure_error(ErrorCode::MissingPath, format!("`{}` does not exist", path))),
+        }
+    }
+    /// The kind of value, used in error messages.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Boolean(_) => "boolean",
+            Value::Integer(_) => "integer",
+            Value::Decimal(_) => "decimal",
+            Value::DateTime(_) => "datetime",
+            Value::Bytes(_) => "bytes",
+            Value::String(_) => "string",
+            Value::List(_) => "list",
+            Value::Dict(_) => "dict",
+        }
+    }
+}
+
+pub(crate) fn invalid_type(path: &str, expected: &str, found: &Value) -> RuntimeError {
+    let message = match path.is_empty() {
+        true => format!("expected {}, found {}", expected, found.kind_name()),
+        false => format!("`{}`: expected {}, found {}", path, expected, found.kind_name()),
+    };
+    RuntimeError::structure_error(ErrorCode::InvalidType, message)
+}
+
+/// The path of a child, keys with a dot are quoted.
+pub(crate) fn child_path(path: &str, key: &str) -> String {
+    let key = match key.contains(&['.', '"'][..]) || key.is_empty() {
+        true => format!("{:?}", key),
+        false => key.to_string(),
+    };
+    match path.is_empty() {
+        true => key,
+        false => format!("{}.{}", path, key),
+    }
+}
+
+macro_rules! try_from_value {
+    ($T:ty) => {
+    impl TryFrom<Value> for $T {
+        type Error = RuntimeError;
+
+        fn try_from(value: Value) -> Result<Self> {
+            <$T>::from_value(&value, "")
+        }
+    }
+    impl TryFrom<&Value> for $T {
+        type Error = RuntimeError;
+
+        fn try_from(value: &Value) -> Result<Self> {
+            <$T>::from_value(value, "")
+        }
+    }
+    };
+    ($($T:ty), +) => {
+        $(try_from_value!($T);)+
+    };
+}
+
+macro_rules! integer2native {
+    ($T:ty) => {
+    impl FromValue for $T {
+        fn from_value(value: &Value, path: &str) -> Result<Self> {
+            match value {
+                Value::Integer(n) => match <$T>::try_from(n.as_ref().deref()) {
+                    Ok(o) => Ok(o),
+                    Err(_) => Err(invalid_type(path, concat!("integer in range of `", stringify!($T), "`"), value)),
+                },
+                _ => Err(invalid_type(path, concat!("integer `", stringify!($T), "`"), value)),
+            }
+        }
     }
+    try_from_value!($T);
+    };
+    ($($T:ty), +) => {
+        $(integer2native!($T);)+
+    };
+}
+
+integer2native![u8, u16, u32, u64, u128, usize];
+integer2native![i8, i16, i32, i64, i128, isize];
+
+impl FromValue for bool {
+    fn from_value(value: &Value, path: &str) -> Result<Self> {
+        match value {
+            Value::Boolean(v) => Ok(*v),
+            _ => Err(invalid_type(path, "boolean", value)),
+        }
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: &Value, path: &str) -> Result<Self> {
+        match value {
+            Value::Integer(n) => n.to_f64().ok_or_else(|| invalid_type(path, "number in range of `f64`", value)),
+            Value::Decimal(n) => Ok(n.to_f64()),
+            _ => Err(invalid_type(path, "number `f64`", value)),
+        }
+    }
+}
+
+impl FromValue for f32 {
+    fn from_value(value: &Value, path: &str) -> Result<Self> {
+        let n = f64::from_value(value, path)?;
+        match n.is_finite() && (n as f32).is_infinite() {
+            true => Err(invalid_type(path, "number in range of `f32`", value)),
+            false => Ok(n as f32),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value, path: &str) -> Result<Self> {
+        match value {
+            Value::String(v) => Ok(v.as_str().to_string()),
+            _ => Err(invalid_type(path, "string", value)),
+        }
+    }
+}
+
+impl FromValue for DateTime {
+    fn from_value(value: &Value, path: &str) -> Result<Self> {
+        match value {
+            Value::DateTime(v) => Ok(**v),
+            _ => Err(invalid_type(path, "datetime", value)),
+        }
+    }
+}
+
+impl FromValue for Bytes {
+    fn from_value(value: &Value, path: &str) -> Result<Self> {
+        match value {
+            Value::Bytes(v) => Ok(v.as_ref().clone()),
+            _ => Err(invalid_type(path, "bytes", value)),
+        }
+    }
+}
+
+impl FromValue for Duration {
+    fn from_value(value: &Value, path: &str) -> Result<Self> {
+        value.as_duration().ok_or_else(|| invalid_type(path, "duration such as `30s`", value))
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value, _: &str) -> Result<Self> {
+        Ok(value.clone())
+    }
+}
+
+try_from_value![bool, f32, f64, String, DateTime, Bytes, Duration];
+
+/// Null reads as `None`, there is no `TryFrom` for options since core already turns any type into an option.
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value, path: &str) -> Result<Self> {
+        match value {
+            Value::Null => Ok(None),
+            _ => T::from_value(value, path).map(Some),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &Value, path: &str) -> Result<Self> {
+        match value {
+            Value::List(list) => {
+                let mut out = Vec::with_capacity(list.count());
+                for (i, item) in list.slots() {
+                    out.push(T::from_value(item.unwrap_or(&Value::Null), &child_path(path, &i.to_string()))?)
+                }
+                Ok(out)
+            }
+            _ => Err(invalid_type(path, "list", value)),
+        }
+    }
+}
+
+impl<T: FromValue> TryFrom<Value> for Vec<T> {
+    type Error = RuntimeError;
+
+    fn try_from(value: Value) -> Result<Self> {
+        Self::from_value(&value, "")
+    }
+}
+
+impl<T: FromValue> TryFrom<&Value> for Vec<T> {
+    type Error = RuntimeError;
+
+    fn try_from(value: &Value) -> Result<Self> {
+        Self::from_value(value, "")
+    }
+}
+
+macro_rules! dict2native {
+    ($M:ident) => {
+    impl<K, V, S> FromValue for $M<K, V, S>
+    where
+        K: From<String> + Eq + Hash,
+        V: FromValue,
+        S: BuildHasher + Default,
+    {
+        fn from_value(value: &Value, path: &str) -> Result<Self> {
+            match value {
+                Value::Dict(dict) => {
+                    let mut out = Self::default();
+                    for (k, v) in dict.iter() {
+                        out.insert(K::from(k.to_owned()), V::from_value(v, &child_path(path, k))?);
+                    }
+                    Ok(out)
+                }
+                _ => Err(invalid_type(path, "dict", value)),
+            }
+        }
+    }
+    impl<K, V, S> TryFrom<Value> for $M<K, V, S>
+    where
+        K: From<String> + Eq + Hash,
+        V: FromValue,
+        S: BuildHasher + Default,
+    {
+        type Error = RuntimeError;
+
+        fn try_from(value: Value) -> Result<Self> {
+            Self::from_value(&value, "")
+        }
+    }
+    impl<K, V, S> TryFrom<&Value> for $M<K, V, S>
+    where
+        K: From<String> + Eq + Hash,
+        V: FromValue,
+        S: BuildHasher + Default,
+    {
+        type Error = RuntimeError;
+
+        fn try_from(value: &Value) -> Result<Self> {
+            Self::from_value(value, "")
+        }
+    }
+    };
+}
+
+dict2native!(HashMap);
+dict2native!(IndexMap);
+
+impl<K, V> FromValue for BTreeMap<K, V>
+where
+    K: From<String> + Ord,
+    V: FromValue,
+{
+    fn from_value(value: &Value, path: &str) -> Result<Self> {
+        match value {
+            Value::Dict(dict) => {
+                let mut out = Self::new();
+                for (k, v) in dict.iter() {
+                    out.insert(K::from(k.to_owned()), V::from_value(v, &child_path(path, k))?);
+                }
+                Ok(out)
+            }
+            _ => Err(invalid_type(path, "dict", value)),
+        }
+    }
+}
+
+#[test]
+fn test_into_native() {
+    use crate::{dict, list};
+    let data = dict! {
+        "server": dict! {
+            "host": "localhost",
+            "port": 8080,
+            "ratio": 0.5,
+            "tags": list!["a", "b"],
+            "limits": dict! {"cpu": 2, "memory": 512}
+        },
+        "pair": list![1, "x"],
+        "none": Value::Null
+    };
+    assert_eq!(data.get_as::<String>("server.host").unwrap(), "localhost");
+    assert_eq!(data.get_as::<u16>("server.port").unwrap(), 8080);
+    assert_eq!(data.get_as::<f32>("server.port").unwrap(), 8080.0);
+    assert_eq!(data.get_as::<Vec<String>>("server.tags").unwrap(), vec!["a", "b"]);
+    assert_eq!(data.get_as::<HashMap<String, u32>>("server.limits").unwrap()["memory"], 512);
+    assert_eq!(data.get_as::<(u8, String)>("pair").unwrap(), (1, String::from("x")));
+    assert_eq!(data.get_as::<Option<bool>>("none").unwrap(), None);
+    assert_eq!(data.get_as::<Option<bool>>("missing").unwrap(), None);
+    assert_eq!(u8::try_from(Value::from(255)).unwrap(), 255);
+    let e = u8::try_from(&Value::from(256)).unwrap_err();
+    assert_eq!(e.to_string(), "E0104: expected integer in range of `u8`, found integer");
+    let e = data.get_as::<Vec<u8>>("server.tags").unwrap_err();
+    assert_eq!(e.to_string(), "E0104: `server.tags.0`: expected integer `u8`, found string");
+    let e = data.get_as::<bool>("missing").unwrap_err();
+    assert_eq!(e.to_string(), "E0106: `missing` does not exist");
+    let e = data.get_as::<bool>("none").unwrap_err();
+    assert_eq!(e.to_string(), "E0104: `none`: expected boolean, found null");
+    // holes read as null
+    let mut sparse = crate::value::List::default();
+    *sparse.ensure_index(crate::value::List::MAX_INDEX.into()).unwrap() = Value::from(1);
+    let e = Value::from(sparse).get_as::<Vec<u8>>("").unwrap_err();
+    assert_eq!(e.to_string(), "E0104: `0`: expected integer `u8`, found null");
+    let mut pair = crate::value::List::default();
+    *pair.ensure_index(1usize.into()).unwrap() = Value::from("x");
+    let e = Value::from(pair).get_as::<(u8, String)>("").unwrap_err();
+    assert_eq!(e.to_string(), "E0104: `0`: expected integer `u8`, found null");
 }
@@ -1,25 +1,49 @@
 use super::*;
 use std::{
     collections::btree_map::Entry,
-    ops::{AddAssign, Neg},
+    iter::once,
+    ops::AddAssign,
 };
 
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone)]
 pub struct List {
     handler: Option<String>,
     value: BTreeMap<usize, Value>,
 }
 
+/// A list with holes is written as its items by index, `[0: 1, 4: 5]`.
 impl Debug for List {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match &self.handler {
             Some(s) => write!(f, "{}", s)?,
             None => (),
         }
-        f.debug_list().entries(self.value.values()).finish()
+        match self.is_dense() {
+            true => f.debug_list().entries(self.value.values()).finish(),
+            false => f.debug_list().entries(self.value.iter().map(|(i, v)| SparseItem(*i, v))).finish(),
+        }
+    }
+}
+
+struct SparseItem<'a>(usize, &'a Value);
+
+impl Debug for SparseItem<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.0)?;
+        Debug::fmt(self.1, f)
     }
 }
 
+/// Lists are compared slot by slot, a hole equals null.
+impl PartialEq for List {
+    fn eq(&self, other: &Self) -> bool {
+        let same = |lhs: &Self, rhs: &Self| lhs.value.iter().all(|(i, v)| rhs.value.get(i).unwrap_or(&Value::Null) == v);
+        self.handler == other.handler && self.length() == other.length() && same(self, other) && same(other, self)
+    }
+}
+
+impl Eq for List {}
+
 impl Default for List {
     fn default() -> Self {
         Self { handler: None, value: Default::default() }
@@ -88,72 +112,206 @@ impl AddAssign<List> for List {
 // }
 
 impl List {
+    pub fn set_handler(&mut self, handler: impl Into<String>) {
+        self.handler = Some(handler.into())
+    }
     pub fn get_handler(&self) -> Option<String> {
         self.handler.to_owned()
     }
-    pub fn get_index(&self, index: &Integer) -> Option<&Value> {
-        match index.get_index() {
-            Some(i_index) => {
-                let u_index = match i_index >= 0 {
-                    true => i_index as usize,
-                    false => 1 + self.length() - i_index.neg() as usize,
-                };
-                self.value.get(&u_index)
-            }
-            None => None,
+    pub fn take_handler(&mut self) -> Option<String> {
+        self.handler.take()
+    }
+    /// The slot of an index, negative indices count from [`List::length`], so `-1` is the last slot.
+    ///
+    /// Returns `None` if a negative index points before the first slot, or an index is above [`List::MAX_INDEX`].
+    pub fn resolve_index(&self, index: isize) -> Option<usize> {
+        match index >= 0 {
+            true => Some(index as usize).filter(|i| *i <= List::MAX_INDEX),
+            false => self.length().checked_sub(index.checked_neg()? as usize),
         }
     }
-
-    pub fn ensure_index(&mut self, index: Integer) -> &'_ mut Value {
-        match index.get_index() {
-            Some(i_index) => {
-                let u_index = match i_index >= 0 {
-                    true => i_index as usize,
-                    false => 1 + self.length() - i_index.neg() as usize,
-                };
-                self.entry(u_index).or_default()
+    pub fn get_index(&self, index: &Integer) -> Option<&Value> {
+        self.value.get(&self.resolve_index(index.get_index()?)?)
+    }
+    /// Get the item at an index or create it as null, a hole is left before an index past the end.
+    pub fn ensure_index(&mut self, index: Integer) -> Result<&'_ mut Value> {
+        match index.get_index().and_then(|i| self.resolve_index(i)) {
+            Some(u_index) => Ok(self.entry(u_index).or_default()),
+            None if index.get_value() > BigInt::from(List::MAX_INDEX) => {
+                Err(RuntimeError::structure_error(ErrorCode::InvalidKey, format!("index `{}` is above the maximum {}", index, List::MAX_INDEX)))
             }
-            None => unreachable!(),
+            None => Err(RuntimeError::structure_error(ErrorCode::NotList, format!("index `{}` is out of range", index))),
         }
     }
 }
 
+/// A list is sparse, items are kept by their index.
+///
+/// Writing past the end, such as `a.5 = x` under a list of two items, leaves a hole at each skipped index.
+/// A hole counts in [`List::length`] and negative indices, [`List::get`] finds nothing there and the dense
+/// form from [`List::as_vec`] holds null in its place.
 impl List {
+    /// The largest index that can be written, so that the holes before an item stay small enough to print.
+    pub const MAX_INDEX: usize = 1_000_000;
+
     pub fn empty() -> Value {
         Value::from(List::default())
     }
-
+    /// The number of slots, holes included, which is the index after the last item.
     pub fn length(&self) -> usize {
-        // match self.value.last_key_value() {
-        //     Some((n, _)) => {*n},
-        //     None => {0}
-        // }
-        match self.value.keys().rev().next() {
-            Some(n) => *n,
+        match self.value.keys().next_back() {
+            Some(n) => n + 1,
             None => 0,
         }
     }
-
+    /// The number of items, holes excluded.
+    pub fn count(&self) -> usize {
+        self.value.len()
+    }
+    /// Whether the list has no holes.
+    pub fn is_dense(&self) -> bool {
+        self.count() == self.length()
+    }
+    /// The dense form, holes are filled with null.
+    ///
+    /// This allocates every slot, walk [`List::iter`] or [`List::slots`] where a list may have large holes.
     pub fn as_vec(&self) -> Vec<Value> {
-        self.value.values().cloned().collect()
+        let mut out = vec![Value::Null; self.length()];
+        for (i, v) in &self.value {
+            out[*i] = v.clone()
+        }
+        out
+    }
+    /// Fill the holes with null.
+    pub fn make_dense(&mut self) {
+        for i in 0..self.length() {
+            self.value.entry(i).or_default();
+        }
+    }
+    /// The index after the last element
+    pub fn next_index(&self) -> usize {
+        self.length()
     }
 
     pub fn entry(&mut self, index: usize) -> Entry<'_, usize, Value> {
         self.value.entry(index)
     }
+    /// Items with their index in order, holes are skipped.
+    pub fn iter(&self) -> std::collections::btree_map::Iter<'_, usize, Value> {
+        self.value.iter()
+    }
+    /// Every slot in order, `None` at a hole.
+    pub fn slots(&self) -> impl Iterator<Item = (usize, Option<&Value>)> + '_ {
+        self.runs().flat_map(|(holes, item)| (0..holes).map(|_| None).chain(once(Some(item)))).enumerate()
+    }
+    /// Items in order with the number of holes before each, a writer fills the holes without a dense copy.
+    pub fn runs(&self) -> impl Iterator<Item = (usize, &Value)> + '_ {
+        let mut next = 0;
+        self.value.iter().map(move |(i, item)| {
+            let holes = i - next;
+            next = i + 1;
+            (holes, item)
+        })
+    }
 
     pub fn get(&self, index: &str) -> Option<&Value> {
-        let i = match isize::from_str(index) {
-            Ok(o) => o,
-            Err(_) => return None,
-        };
-        match i > 0 {
-            true => self.value.get(&(i as usize)),
-            false => self.value.get(&((self.value.len() as isize + i) as usize)),
-        }
+        let u = self.resolve_index(isize::from_str(index).ok()?)?;
+        self.value.get(&u)
+    }
+    pub fn get_mut(&mut self, index: &str) -> Option<&mut Value> {
+        let u = self.resolve_index(isize::from_str(index).ok()?)?;
+        self.value.get_mut(&u)
+    }
+    /// Insert an item at `index` and shift the later items and holes up.
+    pub fn insert(&mut self, index: usize, item: Value) {
+        let later = self.value.split_off(&index);
+        self.value.insert(index, item);
+        self.value.extend(later.into_iter().map(|(k, v)| (k + 1, v)));
+    }
+    /// Remove the item at `index` and shift the later items and holes down.
+    pub fn remove(&mut self, index: &str) -> Option<Value> {
+        let u = self.resolve_index(isize::from_str(index).ok()?)?;
+        let out = self.value.remove(&u)?;
+        let later = self.value.split_off(&u);
+        self.value.extend(later.into_iter().map(|(k, v)| (k - 1, v)));
+        Some(out)
     }
 
     pub fn extend(&mut self, item: impl Into<List>) {
         self.value.extend(item.into().value)
     }
 }
+
+#[test]
+fn test_list() {
+    let mut list = List::from(vec![1, 2]);
+    assert_eq!(list.get("0"), Some(&Value::from(1)));
+    assert_eq!(list.get("-1"), Some(&Value::from(2)));
+    assert_eq!(list.get("-3"), None);
+    *list.ensure_index(Integer::from(4)).unwrap() = Value::from(5);
+    assert_eq!((list.length(), list.count(), list.is_dense()), (5, 3, false));
+    assert_eq!(list.get("3"), None);
+    assert_eq!(list.get("-1"), Some(&Value::from(5)));
+    assert_eq!(list, List::from(vec![Value::from(1), Value::from(2), Value::Null, Value::Null, Value::from(5)]));
+    assert_eq!(list.remove("2"), None);
+    assert_eq!(list.remove("0"), Some(Value::from(1)));
+    assert_eq!(list.as_vec(), vec![Value::from(2), Value::Null, Value::Null, Value::from(5)]);
+    assert!(List::default().ensure_index(Integer::from(-1)).is_err());
+    // a large hole is never filled
+    let mut far = List::default();
+    *far.ensure_index(Integer::from(List::MAX_INDEX)).unwrap() = Value::from(1);
+    assert_ne!(far, list);
+    assert_eq!(far, far.clone());
+    assert_eq!(format!("{:?}", far), "[1000000: 1]");
+    assert_eq!(far.runs().collect::<Vec<_>>(), vec![(List::MAX_INDEX, &Value::from(1))]);
+    // a huge index is rejected
+    let e = far.ensure_index(Integer::from(List::MAX_INDEX + 1)).unwrap_err();
+    assert_eq!(e.to_string(), "E0103: index `1000001` is above the maximum 1000000");
+    assert!(far.ensure_index(Integer::from(9_999_999_999_999usize)).is_err());
+    assert_eq!(far.get("9999999999999"), None);
+}
+
+#[cfg(test)]
+proptest::proptest! {
+    #[test]
+    fn test_list_index(items in proptest::collection::vec(0i32..100, 0..8), index in -10isize..10) {
+        let list = List::from(items.clone());
+        let expected = match index >= 0 {
+            true => items.get(index as usize),
+            false => items.len().checked_sub(-index as usize).and_then(|i| items.get(i)),
+        };
+        let expected = expected.map(|v| Value::from(*v));
+        proptest::prop_assert_eq!(list.get(&index.to_string()), expected.as_ref());
+        proptest::prop_assert_eq!(list.get_index(&Integer::from(index)), expected.as_ref());
+        let value = Value::from(list);
+        proptest::prop_assert_eq!(value.pointer(&index.to_string()), expected.as_ref());
+        proptest::prop_assert_eq!(value.get_value(&[Value::from(index)]), expected.as_ref().unwrap_or(&Value::Null));
+    }
+
+    #[test]
+    fn test_list_holes(writes in proptest::collection::vec((0usize..12, 0i32..100), 1..8)) {
+        let mut list = List::default();
+        for (i, v) in &writes {
+            *list.ensure_index(Integer::from(*i)).unwrap() = Value::from(*v);
+        }
+        let last = writes.iter().map(|(i, _)| *i).max().unwrap();
+        proptest::prop_assert_eq!(list.length(), last + 1);
+        proptest::prop_assert_eq!(list.as_vec().len(), list.length());
+        for i in 0..list.length() {
+            match writes.iter().rev().find(|(j, _)| *j == i) {
+                Some((_, v)) => proptest::prop_assert_eq!(list.get(&i.to_string()), Some(&Value::from(*v))),
+                None => {
+                    proptest::prop_assert_eq!(list.get(&i.to_string()), None);
+                    proptest::prop_assert_eq!(&list.as_vec()[i], &Value::Null);
+                }
+            }
+        }
+        let negative = -(list.length() as isize);
+        proptest::prop_assert_eq!(list.clone().ensure_index(Integer::from(negative)).is_ok(), true);
+        proptest::prop_assert!(list.clone().ensure_index(Integer::from(negative - 1)).is_err());
+        let mut dense = list.clone();
+        dense.make_dense();
+        proptest::prop_assert!(dense.is_dense());
+        proptest::prop_assert_eq!(dense, list);
+    }
+}
@@ -0,0 +1,244 @@
+use super::*;
+
+/// How an incoming value is combined with the existing one.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum MergeStrategy {
+    /// The incoming value replaces the existing one
+    Replace,
+    /// Dicts are merged key by key, the strategy of each key is looked up again
+    Deep,
+    /// Items of the incoming list go after the existing items
+    Append,
+    /// Items of the incoming list go before the existing items
+    Prepend,
+    /// Dicts in the incoming list are merged into the existing dict with the same value at this key or path, other items
+    /// are appended
+    UnionBy(String),
+    /// The existing value is kept and the incoming one is dropped, a missing value is set by the first layer that
+    /// has it
+    Keep,
+}
+
+/// Strategies for merging one value into another, see [`Value::merge_with`].
+///
+/// A strategy set for a path wins over the strategy of the value type. Paths are dotted or JSON pointers
+/// relative to the merged value, a `*` segment matches any key or index.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MergePolicy {
+    /// Used when both values are dicts, [`MergeStrategy::Deep`] by default
+    pub dict: MergeStrategy,
+    /// Used when both values are lists, [`MergeStrategy::Replace`] by default
+    pub list: MergeStrategy,
+    pub paths: Vec<(Vec<String>, MergeStrategy)>,
+    /// An incoming null removes the key instead of setting null
+    pub delete_on_null: bool,
+}
+
+impl Default for MergePolicy {
+    fn default() -> Self {
+        Self { dict: MergeStrategy::Deep, list: MergeStrategy::Replace, paths: vec![], delete_on_null: false }
+    }
+}
+
+impl MergePolicy {
+    /// Use `strategy` for the value at `path`, a later call for the same path wins.
+    pub fn set_path(&mut self, path: &str, strategy: MergeStrategy) -> Result<()> {
+        self.paths.insert(0, (parse_path(path)?, strategy));
+        Ok(())
+    }
+    /// Apply a `#merge name(key) "path"` directive.
+    ///
+    /// An empty path sets the strategy of every dict or list instead, `#merge delete_null ""` turns on `delete_on_null`
+    /// for the whole document and takes no other path.
+    pub fn apply_directive(&mut self, name: &str, key: Option<&str>, path: &str) -> Result<()> {
+        let strategy = match (name, key) {
+            ("replace", None) => MergeStrategy::Replace,
+            ("deep", None) => MergeStrategy::Deep,
+            ("append", None) => MergeStrategy::Append,
+            ("prepend", None) => MergeStrategy::Prepend,
+            ("keep", None) => MergeStrategy::Keep,
+            ("union", Some(key)) => MergeStrategy::UnionBy(key.to_string()),
+            ("delete_null", None) if path.is_empty() => {
+                self.delete_on_null = true;
+                return Ok(());
+            }
+            ("union", None) => return Err(invalid_merge("`union` needs a key field, such as `union(id)`")),
+            ("delete_null", None) => return Err(invalid_merge("`delete_null` applies to the whole document, use `#merge delete_null \"\"`")),
+            _ => return Err(invalid_merge(&format!("unknown merge strategy `{}`", name))),
+        };
+        if !path.is_empty() {
+            return self.set_path(path, strategy);
+        }
+        match strategy {
+            MergeStrategy::Deep => self.dict = strategy,
+            MergeStrategy::Append | MergeStrategy::Prepend | MergeStrategy::UnionBy(_) => self.list = strategy,
+            _ => {
+                self.dict = strategy.clone();
+                self.list = strategy
+            }
+        }
+        Ok(())
+    }
+    fn strategy(&self, path: &[String], base: &Value, incoming: &Value) -> &MergeStrategy {
+        let matched = self.paths.iter().find(|(p, _)| p.len() == path.len() && p.iter().zip(path).all(|(p, s)| p == "*" || p == s));
+        match (matched, base, incoming) {
+            (Some((_, strategy)), ..) => strategy,
+            (None, Value::Dict(_), Value::Dict(_)) => &self.dict,
+            (None, Value::List(_), Value::List(_)) => &self.list,
+            _ => &MergeStrategy::Replace,
+        }
+    }
+}
+
+fn invalid_merge(message: &str) -> RuntimeError {
+    RuntimeError::structure_error(ErrorCode::InvalidMerge, message)
+}
+
+impl Value {
+    /// Merge in a configuration property source, dicts are merged deeply and other values are replaced.
+    pub fn merge(&mut self, incoming: Value) {
+        self.merge_with(incoming, &MergePolicy::default())
+    }
+    /// Merge in a configuration property source with the strategies of `policy`.
+    ///
+    /// A strategy for lists applies to lists on both sides only, otherwise the incoming value replaces the existing one.
+    pub fn merge_with(&mut self, incoming: Value, policy: &MergePolicy) {
+        merge_at(self, incoming, policy, &mut vec![])
+    }
+}
+
+fn merge_at(base: &mut Value, incoming: Value, policy: &MergePolicy, path: &mut Vec<String>) {
+    match (policy.strategy(path, base, &incoming), base, incoming) {
+        (MergeStrategy::Keep, ..) => (),
+        (MergeStrategy::Deep, Value::Dict(lhs), Value::Dict(rhs)) => {
+            if let Some(handler) = rhs.get_handler() {
+                lhs.set_handler(handler)
+            }
+            for (key, value) in rhs.iter() {
+                path.push(key.to_string());
+                match (lhs.get_mut(key), value) {
+                    (_, Value::Null) if policy.delete_on_null => {
+                        lhs.remove(key);
+                    }
+                    (Some(old), _) => merge_at(old, value.clone(), policy, path),
+                    (None, _) => {
+                        lhs.insert(key.to_string(), value.clone());
+                    }
+                }
+                path.pop();
+            }
+        }
+        // holes are kept as holes
+        (MergeStrategy::Append, Value::List(lhs), Value::List(rhs)) => {
+            let offset = lhs.length();
+            for (i, item) in rhs.iter() {
+                *lhs.entry(offset + i).or_default() = item.clone()
+            }
+        }
+        (MergeStrategy::Prepend, Value::List(lhs), Value::List(rhs)) => {
+            let mut items = List::default();
+            if let Some(handler) = lhs.get_handler() {
+                items.set_handler(handler)
+            }
+            for (i, item) in rhs.iter() {
+                *items.entry(*i).or_default() = item.clone()
+            }
+            let offset = rhs.length();
+            for (i, item) in lhs.iter() {
+                *items.entry(offset + i).or_default() = item.clone()
+            }
+            **lhs = items
+        }
+        // holes of the incoming list are dropped
+        (MergeStrategy::UnionBy(field), Value::List(lhs), Value::List(rhs)) => {
+            let field = field.to_owned();
+            // the first item of each key, keys are told apart by their debug form
+            let mut index = HashMap::new();
+            for (i, item) in lhs.iter() {
+                if let Some(id) = item.pointer(&field) {
+                    index.entry(format!("{:?}", id)).or_insert(*i);
+                }
+            }
+            for (_, item) in rhs.iter() {
+                let id = item.pointer(&field).map(|id| format!("{:?}", id));
+                match id.as_ref().and_then(|id| index.get(id)) {
+                    Some(&i) => {
+                        path.push(i.to_string());
+                        merge_at(lhs.entry(i).or_default(), item.clone(), policy, path);
+                        path.pop();
+                    }
+                    None => {
+                        let i = lhs.length();
+                        if let Some(id) = id {
+                            index.insert(id, i);
+                        }
+                        *lhs.entry(i).or_default() = item.clone()
+                    }
+                }
+            }
+        }
+        (_, base, incoming) => *base = incoming,
+    }
+}
+
+#[test]
+fn test_merge() {
+    use crate::{dict, list};
+    let base = dict! {
+        "name": "base",
+        "debug": true,
+        "plugins": list!["math"],
+        "folders": list!["posts"],
+        "servers": list![dict! {"name": "alpha", "port": 1}, dict! {"name": "beta", "port": 2}],
+        "db": dict! {"host": "localhost", "port": 5432}
+    };
+    let incoming = dict! {
+        "name": "prod",
+        "debug": Value::Null,
+        "plugins": list!["analytics"],
+        "folders": list!["drafts"],
+        "servers": list![dict! {"name": "beta", "port": 3}, dict! {"name": "gamma", "port": 4}],
+        "db": dict! {"host": "db.internal"},
+        "source": list!["src"]
+    };
+    let mut out = base.clone();
+    out.merge(incoming.clone());
+    assert_eq!(out.pointer("plugins"), Some(&list!["analytics"]));
+    assert_eq!(out.pointer("db"), Some(&dict! {"host": "db.internal", "port": 5432}));
+    assert_eq!(out.pointer("debug"), Some(&Value::Null));
+    let mut policy = MergePolicy::default();
+    policy.apply_directive("append", None, "plugins").unwrap();
+    policy.apply_directive("prepend", None, "folders").unwrap();
+    policy.apply_directive("union", Some("name"), "servers").unwrap();
+    policy.apply_directive("keep", None, "source").unwrap();
+    policy.apply_directive("replace", None, "db").unwrap();
+    policy.apply_directive("delete_null", None, "").unwrap();
+    let mut out = base.clone();
+    out.merge_with(incoming, &policy);
+    assert_eq!(out.pointer("name"), Some(&Value::from("prod")));
+    assert_eq!(out.pointer("plugins"), Some(&list!["math", "analytics"]));
+    assert_eq!(out.pointer("folders"), Some(&list!["drafts", "posts"]));
+    assert_eq!(out.pointer("servers"), Some(&list![dict! {"name": "alpha", "port": 1}, dict! {"name": "beta", "port": 3}, dict! {"name": "gamma", "port": 4}]));
+    assert_eq!(out.pointer("db"), Some(&dict! {"host": "db.internal"}));
+    assert!(!out.has_key("debug"));
+    assert_eq!(out.pointer("source"), Some(&list!["src"]));
+    // the first layer sets a kept value, the later ones do not change it
+    let mut out = dict! {"source": list!["posts"]};
+    out.merge_with(dict! {"source": list!["drafts"]}, &policy);
+    assert_eq!(out.pointer("source"), Some(&list!["posts"]));
+    policy.apply_directive("append", None, "").unwrap();
+    policy.set_path("*.tags", MergeStrategy::Replace).unwrap();
+    let mut out = dict! {"a": list![1], "b": dict! {"tags": list![1]}};
+    out.merge_with(dict! {"a": list![2], "b": dict! {"tags": list![2]}}, &policy);
+    assert_eq!(out, dict! {"a": list![1, 2], "b": dict! {"tags": list![2]}});
+    let e = policy.apply_directive("union", None, "servers").unwrap_err();
+    assert_eq!(e.to_string(), "E0009: `union` needs a key field, such as `union(id)`");
+    assert!(policy.apply_directive("shuffle", None, "").is_err());
+    let e = policy.apply_directive("delete_null", None, "servers").unwrap_err();
+    assert_eq!(e.to_string(), "E0009: `delete_null` applies to the whole document, use `#merge delete_null \"\"`");
+    // an item added by the union is found by later items
+    let mut out = list![dict! {"id": 1, "v": 1}];
+    let policy = MergePolicy { list: MergeStrategy::UnionBy(String::from("id")), ..MergePolicy::default() };
+    out.merge_with(list![dict! {"id": 2, "v": 2}, dict! {"id": 2, "w": 3}, dict! {"id": 1, "v": 4}], &policy);
+    assert_eq!(out, list![dict! {"id": 1, "v": 4}, dict! {"id": 2, "v": 2, "w": 3}]);
+}
@@ -1,5 +1,7 @@
 mod access;
+mod bytes;
 mod check;
+mod datetime;
 mod decimal;
 mod dict;
 mod from_native;
@@ -7,15 +9,31 @@ mod integer;
 mod into_ast;
 mod into_native;
 mod list;
+mod merge;
+mod patch;
+mod provenance;
 mod string;
+mod unit;
 
-pub use decimal::Decimal;
+pub use access::{parse_path, Query, QueryMatch};
+pub use bytes::Bytes;
+pub use datetime::{Date, DateTime, Offset, Time};
+pub use decimal::{Decimal, IeeeSpecial};
 pub use dict::Dict;
-pub use from_native::parse_number;
+pub use from_native::{is_type_suffix, parse_byte, parse_number};
 pub use integer::Integer;
+pub use into_native::FromValue;
+pub(crate) use into_ast::{is_list_scope, is_section};
+pub(crate) use into_native::invalid_type;
 pub use list::List;
-pub use string::{Text, TextDelimiter};
+pub use merge::{MergePolicy, MergeStrategy};
+pub use patch::{Patch, PatchOperation};
+pub use provenance::{Definition, Layer, Provenance};
+pub use string::{dedent, unescape, unescape_all, Text, TextDelimiter};
+pub(crate) use string::escape;
+pub use unit::parse_unit;
 
+use crate::{ErrorCode, Result, RuntimeError};
 use bigdecimal::BigDecimal;
 use indexmap::IndexMap;
 use num::{BigInt, BigUint};
@@ -33,6 +51,8 @@ pub enum Value {
     Boolean(bool),
     Integer(Box<Integer>),
     Decimal(Box<Decimal>),
+    DateTime(Box<DateTime>),
+    Bytes(Box<Bytes>),
     String(Box<Text>),
     List(Box<List>),
     Dict(Box<Dict>),
@@ -51,6 +71,8 @@ impl Debug for Value {
             Value::Boolean(v) => Display::fmt(v, f),
             Value::Integer(v) => Display::fmt(v, f),
             Value::Decimal(v) => Display::fmt(v, f),
+            Value::DateTime(v) => Display::fmt(v, f),
+            Value::Bytes(v) => Display::fmt(v, f),
             Value::String(v) => Display::fmt(v, f),
             Value::List(v) => Debug::fmt(v, f),
             Value::Dict(v) => Debug::fmt(v, f),
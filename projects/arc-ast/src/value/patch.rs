@@ -0,0 +1,398 @@
+use crate::{
+    dict,
+    value::{parse_path, Dict, List},
+    ErrorCode, Result, RuntimeError, Value,
+};
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+};
+
+/// One change of a [`Patch`], `path` holds the keys and indices from the root.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PatchOperation {
+    /// Add a key, or insert an item and shift the later items up, an index past the end leaves holes
+    Add { path: Vec<String>, value: Value },
+    /// Remove a key, or remove an item and shift the later items down
+    Remove { path: Vec<String> },
+    /// Change the value at an existing path
+    Replace { path: Vec<String>, value: Value },
+}
+
+/// Operations that turn one value into another, applied in order as in RFC 6902.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Patch {
+    pub operations: Vec<PatchOperation>,
+}
+
+impl PatchOperation {
+    pub fn get_path(&self) -> &[String] {
+        match self {
+            PatchOperation::Add { path, .. } | PatchOperation::Remove { path } | PatchOperation::Replace { path, .. } => path,
+        }
+    }
+    /// The path as JSON pointer, `~` is written as `~0` and `/` as `~1`.
+    pub fn pointer(&self) -> String {
+        let mut out = String::new();
+        for token in self.get_path() {
+            out.push('/');
+            out.push_str(&token.replace('~', "~0").replace('/', "~1"))
+        }
+        out
+    }
+    fn op(&self) -> &'static str {
+        match self {
+            PatchOperation::Add { .. } => "add",
+            PatchOperation::Remove { .. } => "remove",
+            PatchOperation::Replace { .. } => "replace",
+        }
+    }
+}
+
+/// `add /servers/2`
+impl Display for PatchOperation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.op(), self.pointer())
+    }
+}
+
+impl Patch {
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+    /// The patch as RFC 6902 document, a list of `{op, path, value}` dicts.
+    pub fn to_json_patch(&self) -> Value {
+        let items = self.operations.iter().map(|operation| match operation {
+            PatchOperation::Remove { .. } => dict! {"op": operation.op(), "path": operation.pointer()},
+            PatchOperation::Add { value, .. } | PatchOperation::Replace { value, .. } => {
+                dict! {"op": operation.op(), "path": operation.pointer(), "value": value.clone()}
+            }
+        });
+        Value::from(items.collect::<Vec<_>>())
+    }
+    /// Read a RFC 6902 document, only `add`, `remove` and `replace` are supported.
+    pub fn from_json_patch(document: &Value) -> Result<Patch> {
+        let items = match document {
+            Value::List(list) => list,
+            _ => return Err(invalid_patch(format!("a json patch must be a list, found {}", document.kind_name()))),
+        };
+        let mut operations = Vec::with_capacity(items.count());
+        for (_, item) in items.slots() {
+            let item = item.unwrap_or(&Value::Null);
+            let field = |key: &str| item.pointer(key).ok_or_else(|| invalid_patch(format!("missing `{}` in {:?}", key, item)));
+            let path = match field("path")? {
+                Value::String(s) if s.as_str().is_empty() || s.as_str().starts_with('/') => parse_path(s.as_str())?,
+                other => return Err(invalid_patch(format!("`path` must be a json pointer, found {:?}", other))),
+            };
+            let operation = match field("op")? {
+                Value::String(s) if s.as_str() == "add" => PatchOperation::Add { path, value: field("value")?.clone() },
+                Value::String(s) if s.as_str() == "remove" => PatchOperation::Remove { path },
+                Value::String(s) if s.as_str() == "replace" => PatchOperation::Replace { path, value: field("value")?.clone() },
+                other => return Err(invalid_patch(format!("unsupported op {:?}", other))),
+            };
+            operations.push(operation)
+        }
+        Ok(Patch { operations })
+    }
+}
+
+fn invalid_patch(message: impl Into<String>) -> RuntimeError {
+    RuntimeError::structure_error(ErrorCode::InvalidPatch, message)
+}
+
+impl Value {
+    /// The operations that turn `self` into `other`.
+    ///
+    /// Keys are removed in their old order and added in their new order. Key order is not part of a patch, a dict
+    /// that only changed its key order has no operations and applying a patch keeps the order of the existing keys.
+    ///
+    /// Lists without holes skip the equal items at both ends and compare the rest item by item, the extra items are
+    /// added or removed before the equal end. Sparse lists are compared by slot, a hole is not equal to null and an
+    /// item that became a hole replaces the whole list. A value whose kind or handler changed is replaced as a whole.
+    pub fn diff(&self, other: &Value) -> Patch {
+        let mut operations = vec![];
+        diff_at(self, other, &mut vec![], &mut operations);
+        Patch { operations }
+    }
+    /// Apply the operations in order, stops at the first one that fails.
+    pub fn apply_patch(&mut self, patch: &Patch) -> Result<()> {
+        for operation in &patch.operations {
+            self.apply_operation(operation).map_err(|e| match e {
+                RuntimeError::StructureError(d) => invalid_patch(format!("{}: {}", operation, d.message)),
+                _ => e,
+            })?
+        }
+        Ok(())
+    }
+    /// The RFC 7386 merge patch that turns `self` into `other`.
+    ///
+    /// A merge patch can not address list items or set null in a dict, a changed list is replaced as a whole and a
+    /// null in `other` removes the key.
+    pub fn merge_patch(&self, other: &Value) -> Value {
+        match (self, other) {
+            (Value::Dict(lhs), Value::Dict(rhs)) if lhs.get_handler() == rhs.get_handler() => {
+                let mut out = Dict::default();
+                for (key, _) in lhs.iter().filter(|(key, _)| rhs.get(key).is_none()) {
+                    out.insert(key.to_string(), Value::Null);
+                }
+                for (key, new) in rhs.iter() {
+                    match lhs.get(key) {
+                        Some(old) if old == new => (),
+                        Some(old) => {
+                            out.insert(key.to_string(), old.merge_patch(new));
+                        }
+                        None => {
+                            out.insert(key.to_string(), new.clone());
+                        }
+                    }
+                }
+                Value::from(out)
+            }
+            _ => other.clone(),
+        }
+    }
+    /// Apply a RFC 7386 merge patch, null removes a key and a dict is merged into a dict.
+    pub fn apply_merge_patch(&mut self, patch: &Value) {
+        let patch = match patch {
+            Value::Dict(dict) => dict,
+            _ => {
+                *self = patch.clone();
+                return;
+            }
+        };
+        if !matches!(self, Value::Dict(_)) {
+            *self = Dict::empty()
+        }
+        if let Value::Dict(dict) = self {
+            for (key, value) in patch.iter() {
+                match value {
+                    Value::Null => {
+                        dict.remove(key);
+                    }
+                    _ => dict.entry(key.to_string()).or_default().apply_merge_patch(value),
+                }
+            }
+        }
+    }
+
+    fn apply_operation(&mut self, operation: &PatchOperation) -> Result<()> {
+        let (last, parents) = match operation.get_path().split_last() {
+            Some(s) => s,
+            None => {
+                *self = match operation {
+                    PatchOperation::Add { value, .. } | PatchOperation::Replace { value, .. } => value.clone(),
+                    PatchOperation::Remove { .. } => Value::Null,
+                };
+                return Ok(());
+            }
+        };
+        let mut parent = self;
+        for token in parents {
+            parent = match parent {
+                Value::Dict(dict) => dict.get_mut(token),
+                Value::List(list) => list.get_mut(token),
+                _ => None,
+            }
+            .ok_or_else(|| invalid_patch("the parent does not exist"))?;
+        }
+        match (operation, parent) {
+            (PatchOperation::Add { value, .. }, Value::Dict(dict)) => {
+                dict.insert(last.to_string(), value.clone());
+            }
+            (PatchOperation::Add { value, .. }, Value::List(list)) => {
+                match last.as_str() {
+                    "-" => list.insert(list.length(), value.clone()),
+                    _ => match list_slot(list, last).ok_or_else(|| invalid_patch("index out of range"))? {
+                        index if index <= list.length() => list.insert(index, value.clone()),
+                        index => *list.entry(index).or_default() = value.clone(),
+                    },
+                }
+            }
+            (PatchOperation::Replace { value, .. }, Value::Dict(dict)) => match dict.get_mut(last) {
+                Some(s) => *s = value.clone(),
+                None => return Err(invalid_patch("the key does not exist")),
+            },
+            // a hole can be replaced
+            (PatchOperation::Replace { value, .. }, Value::List(list)) => {
+                let index = list_slot(list, last).filter(|i| *i < list.length()).ok_or_else(|| invalid_patch("index out of range"))?;
+                *list.entry(index).or_default() = value.clone()
+            }
+            (PatchOperation::Remove { .. }, Value::Dict(dict)) => {
+                dict.remove(last).ok_or_else(|| invalid_patch("the key does not exist"))?;
+            }
+            (PatchOperation::Remove { .. }, Value::List(list)) => {
+                list.remove(last).ok_or_else(|| invalid_patch("the item does not exist"))?;
+            }
+            (_, parent) => return Err(invalid_patch(format!("can not change a child of {}", parent.kind_name()))),
+        }
+        Ok(())
+    }
+}
+
+fn list_slot(list: &List, token: &str) -> Option<usize> {
+    list.resolve_index(token.parse().ok()?)
+}
+
+fn diff_at(old: &Value, new: &Value, path: &mut Vec<String>, out: &mut Vec<PatchOperation>) {
+    match (old, new) {
+        _ if identical(old, new) => (),
+        (Value::Dict(lhs), Value::Dict(rhs)) if lhs.get_handler() == rhs.get_handler() => {
+            for (key, _) in lhs.iter().filter(|(key, _)| rhs.get(key).is_none()) {
+                out.push(PatchOperation::Remove { path: child(path, key) })
+            }
+            for (key, item) in rhs.iter() {
+                match lhs.get(key) {
+                    Some(old) => {
+                        path.push(key.to_string());
+                        diff_at(old, item, path, out);
+                        path.pop();
+                    }
+                    None => out.push(PatchOperation::Add { path: child(path, key), value: item.clone() }),
+                }
+            }
+        }
+        (Value::List(lhs), Value::List(rhs)) if lhs.get_handler() == rhs.get_handler() && lhs.is_dense() && rhs.is_dense() => {
+            diff_dense(lhs, rhs, path, out)
+        }
+        // an item can not be turned into a hole, the list is replaced
+        (Value::List(lhs), Value::List(rhs)) if lhs.get_handler() == rhs.get_handler() && !lhs.iter().any(|(i, _)| *i < rhs.length() && rhs.get(&i.to_string()).is_none()) => {
+            diff_sparse(lhs, rhs, path, out)
+        }
+        _ => out.push(PatchOperation::Replace { path: path.clone(), value: new.clone() }),
+    }
+}
+
+/// The items equal at the start and at the end are skipped, the rest is compared item by item.
+fn diff_dense(lhs: &List, rhs: &List, path: &mut Vec<String>, out: &mut Vec<PatchOperation>) {
+    let old: Vec<&Value> = lhs.iter().map(|(_, v)| v).collect();
+    let new: Vec<&Value> = rhs.iter().map(|(_, v)| v).collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| identical(a, b)).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| identical(a, b)).count();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+    for (offset, (a, b)) in old.iter().zip(new).enumerate() {
+        path.push((prefix + offset).to_string());
+        diff_at(a, b, path, out);
+        path.pop();
+    }
+    // inserted before the equal suffix, which moves up
+    for (offset, item) in new.iter().enumerate().skip(old.len()) {
+        out.push(PatchOperation::Add { path: child(path, &(prefix + offset).to_string()), value: (*item).clone() })
+    }
+    for offset in (new.len()..old.len()).rev() {
+        out.push(PatchOperation::Remove { path: child(path, &(prefix + offset).to_string()) })
+    }
+}
+
+/// Only the slots with an item on either side are visited, a hole in `lhs` is replaced.
+fn diff_sparse(lhs: &List, rhs: &List, path: &mut Vec<String>, out: &mut Vec<PatchOperation>) {
+    let slots: BTreeSet<usize> = lhs.iter().chain(rhs.iter()).map(|(i, _)| *i).collect();
+    for &index in slots.range(..rhs.length()) {
+        let item = match rhs.get(&index.to_string()) {
+            Some(s) => s,
+            None => continue,
+        };
+        match lhs.get(&index.to_string()) {
+            Some(old) => {
+                path.push(index.to_string());
+                diff_at(old, item, path, out);
+                path.pop();
+            }
+            None if index < lhs.length() => out.push(PatchOperation::Replace { path: child(path, &index.to_string()), value: item.clone() }),
+            None => out.push(PatchOperation::Add { path: child(path, &index.to_string()), value: item.clone() }),
+        }
+    }
+    for &index in slots.range(rhs.length()..).rev() {
+        out.push(PatchOperation::Remove { path: child(path, &index.to_string()) })
+    }
+}
+
+/// Same as `==`, except that a hole only equals a hole.
+fn identical(old: &Value, new: &Value) -> bool {
+    match (old, new) {
+        (Value::List(lhs), Value::List(rhs)) => {
+            lhs.get_handler() == rhs.get_handler() && lhs.count() == rhs.count() && lhs.iter().zip(rhs.iter()).all(|((i, a), (j, b))| i == j && identical(a, b))
+        }
+        (Value::Dict(lhs), Value::Dict(rhs)) => {
+            lhs.get_handler() == rhs.get_handler() && lhs.length() == rhs.length() && lhs.iter().all(|(key, a)| rhs.get(key).is_some_and(|b| identical(a, b)))
+        }
+        _ => old == new,
+    }
+}
+
+fn child(path: &[String], token: &str) -> Vec<String> {
+    let mut out = path.to_vec();
+    out.push(token.to_string());
+    out
+}
+
+#[test]
+fn test_patch() {
+    use crate::list;
+    let old = dict! {
+        "name": "blog",
+        "debug": true,
+        "db": dict! {"host": "localhost", "port": 5432},
+        "plugins": list!["math", "highlight", "toc"]
+    };
+    let new = dict! {
+        "db": dict! {"port": 6543, "host": "localhost"},
+        "name": "blog",
+        "plugins": list!["math", "mermaid"],
+        "tags": list![]
+    };
+    let patch = old.diff(&new);
+    let steps: Vec<String> = patch.operations.iter().map(|o| o.to_string()).collect();
+    assert_eq!(steps, vec!["remove /debug", "replace /db/port", "replace /plugins/1", "remove /plugins/2", "add /tags"]);
+    let mut out = old.clone();
+    out.apply_patch(&patch).unwrap();
+    assert_eq!(out, new);
+    assert!(new.diff(&out).is_empty());
+    // round trip through a json patch document
+    let document = patch.to_json_patch();
+    assert_eq!(document.pointer("0"), Some(&dict! {"op": "remove", "path": "/debug"}));
+    assert_eq!(Patch::from_json_patch(&document).unwrap(), patch);
+    // sparse lists compare by slot
+    let mut sparse = List::default();
+    sparse.ensure_index(2.into()).map(|v| *v = Value::from(1)).unwrap();
+    let sparse = Value::from(sparse);
+    let mut out = list![0];
+    out.apply_patch(&out.diff(&sparse)).unwrap();
+    assert_eq!(out, sparse);
+    // a large hole is not filled
+    let mut far = dict! {"a": list![0]};
+    far.set_path("a.1000000", 1).unwrap();
+    let steps: Vec<String> = dict! {"a": list![0]}.diff(&far).operations.iter().map(|o| o.to_string()).collect();
+    assert_eq!(steps, vec!["add /a/1000000"]);
+    let mut out = dict! {"a": list![0]};
+    out.apply_patch(&out.diff(&far)).unwrap();
+    assert_eq!(out, far);
+    // a hole is not null
+    let mut hole = Value::Null;
+    hole.set_path("1", 1).unwrap();
+    let steps: Vec<String> = hole.diff(&list![Value::Null, 1]).operations.iter().map(|o| o.to_string()).collect();
+    assert_eq!(steps, vec!["replace /0"]);
+    let steps: Vec<String> = list![Value::Null, 1].diff(&hole).operations.iter().map(|o| o.to_string()).collect();
+    assert_eq!(steps, vec!["replace "]);
+    let mut out = list![Value::Null, 1];
+    out.apply_patch(&out.diff(&hole)).unwrap();
+    assert_eq!(out.pointer("/0"), None);
+    // the equal ends of a list are skipped
+    let long = list![1, 2, 3, 4];
+    let steps: Vec<String> = long.diff(&list![0, 1, 2, 3, 4]).operations.iter().map(|o| o.to_string()).collect();
+    assert_eq!(steps, vec!["add /0"]);
+    let steps: Vec<String> = long.diff(&list![1, 4]).operations.iter().map(|o| o.to_string()).collect();
+    assert_eq!(steps, vec!["remove /2", "remove /1"]);
+    let mut out = long.clone();
+    out.apply_patch(&long.diff(&list![1, 5, 6, 4])).unwrap();
+    assert_eq!(out, list![1, 5, 6, 4]);
+    // failed operations
+    let remove = Patch { operations: vec![PatchOperation::Remove { path: vec!["x".to_string()] }] };
+    assert!(old.clone().apply_patch(&remove).is_err());
+    // merge patch
+    let merge = old.merge_patch(&new);
+    assert_eq!(merge, dict! {"debug": Value::Null, "db": dict! {"port": 6543}, "plugins": list!["math", "mermaid"], "tags": list![]});
+    let mut out = old.clone();
+    out.apply_merge_patch(&merge);
+    assert_eq!(out, new);
+}
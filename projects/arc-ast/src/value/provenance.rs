@@ -0,0 +1,227 @@
+use crate::{value::parse_path, TextRange, Value};
+use indexmap::IndexMap;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    ops::Bound,
+    path::PathBuf,
+};
+
+/// The layer that set a value.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Layer {
+    /// Written in the document itself
+    Document,
+    /// `#extend "file"`
+    Extend(PathBuf),
+    /// `#import "file" as ns`, copied by a cite
+    Import(PathBuf),
+    /// In-code defaults of a loader
+    Defaults,
+    /// A file stacked by a loader
+    File(PathBuf),
+    /// An environment variable, by name
+    Env(String),
+    /// A `key.path=value` override
+    Override(String),
+}
+
+/// One definition of a value, `file` and `range` are missing if the value was not written in arc text.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Definition {
+    pub file: Option<PathBuf>,
+    pub range: Option<TextRange>,
+    pub layer: Layer,
+}
+
+/// Definitions of each value path, in the order they were set.
+///
+/// A path is recorded for every leaf that was written, dicts are recorded too if they were written as literal.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Provenance {
+    paths: IndexMap<Vec<String>, Vec<Definition>>,
+}
+
+impl Display for Layer {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Layer::Document => write!(f, "document"),
+            Layer::Extend(path) => write!(f, "extend {}", path.display()),
+            Layer::Import(path) => write!(f, "import {}", path.display()),
+            Layer::Defaults => write!(f, "defaults"),
+            Layer::File(path) => write!(f, "file {}", path.display()),
+            Layer::Env(name) => write!(f, "env {}", name),
+            Layer::Override(arg) => write!(f, "override `{}`", arg),
+        }
+    }
+}
+
+/// `file:line:column (layer)`
+impl Display for Definition {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match (&self.file, &self.range) {
+            (Some(file), Some(range)) => write!(f, "{}:{}:{} ", file.display(), range.start.0, range.start.1)?,
+            (Some(file), None) => write!(f, "{} ", file.display())?,
+            (None, Some(range)) => write!(f, "{}:{} ", range.start.0, range.start.1)?,
+            (None, None) => (),
+        }
+        write!(f, "({})", self.layer)
+    }
+}
+
+impl Provenance {
+    /// Record that the value at `path` was set.
+    pub fn define(&mut self, path: Vec<String>, definition: Definition) {
+        self.paths.entry(path).or_default().push(definition)
+    }
+    /// Record the same definition for every leaf of `value`, which was set at `path`.
+    pub fn define_all(&mut self, path: &[String], value: &Value, definition: &Definition) {
+        let mut path = path.to_vec();
+        for_leaves(value, &mut path, &mut |leaf, _| self.define(leaf.to_vec(), definition.clone()))
+    }
+    /// Record a layer that was merged at `path`, `source` holds where the values of `incoming` came from.
+    ///
+    /// A leaf counts as set if the merge changed it or if it equals the incoming value, so a kept value is skipped.
+    /// Lists are leaves, the definitions of their items are only taken if the list was taken as a whole,
+    /// otherwise the items it changed are recorded with `definition`.
+    /// Definitions of `source` in the document layer are moved to the layer of `definition`, which is also used for
+    /// leaves that `source` knows nothing about.
+    pub fn merge_layer(&mut self, path: &[String], before: &Value, after: &Value, incoming: &Value, source: &Provenance, definition: &Definition) {
+        // sorted, so the paths under a leaf are one range
+        let sorted: BTreeMap<&[String], &Vec<Definition>> = source.paths.iter().map(|(k, v)| (k.as_slice(), v)).collect();
+        for_leaves(incoming, &mut vec![], &mut |leaf, value| {
+            let new = lookup(after, leaf);
+            if new != lookup(before, leaf) || new == Some(value) {
+                // items of an appended or united list moved, so only the list itself is explained
+                let whole = new == Some(value);
+                let keys: Vec<(&[String], &Vec<Definition>)> = match whole {
+                    true => sorted.range::<[String], _>((Bound::Included(leaf), Bound::Unbounded)).take_while(|(key, _)| key.starts_with(leaf)).map(|(k, v)| (*k, *v)).collect(),
+                    false => source.paths.get_key_value(leaf).map(|(k, v)| (k.as_slice(), v)).into_iter().collect(),
+                };
+                let mut found = false;
+                for (key, definitions) in keys {
+                    let full: Vec<String> = path.iter().chain(key.iter()).cloned().collect();
+                    for item in definitions {
+                        let mut item = item.clone();
+                        if item.layer == Layer::Document {
+                            item.layer = definition.layer.clone()
+                        }
+                        self.define(full.clone(), item);
+                    }
+                    found = true;
+                }
+                if !found {
+                    self.define(path.iter().chain(leaf.iter()).cloned().collect(), definition.clone())
+                }
+                if let (false, Some(new)) = (whole, new) {
+                    let mut inner = leaf.to_vec();
+                    for_changed(new, lookup(before, leaf), &mut inner, &mut |item| {
+                        self.define(path.iter().chain(item.iter()).cloned().collect(), definition.clone())
+                    })
+                }
+            }
+        })
+    }
+
+    /// The chain of definitions of a dotted path or JSON pointer, the first one was overridden by the later ones.
+    ///
+    /// A path inside a value that was set as a whole, such as an item of a list, is explained by that value.
+    pub fn explain(&self, path: &str) -> Vec<&Definition> {
+        let mut path = match parse_path(path) {
+            Ok(o) => o,
+            Err(_) => return vec![],
+        };
+        loop {
+            if let Some(s) = self.paths.get(&path) {
+                return s.iter().collect();
+            }
+            if path.pop().is_none() {
+                return vec![];
+            }
+        }
+    }
+    /// The definition that set the value at a path last.
+    pub fn get(&self, path: &str) -> Option<&Definition> {
+        self.explain(path).pop()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&[String], &[Definition])> {
+        self.paths.iter().map(|(k, v)| (k.as_slice(), v.as_slice()))
+    }
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+}
+
+/// Visit every leaf, a dict with items is not a leaf.
+fn for_leaves(value: &Value, path: &mut Vec<String>, f: &mut dyn FnMut(&[String], &Value)) {
+    match value {
+        Value::Dict(dict) if dict.length() > 0 => {
+            for (key, item) in dict.iter() {
+                path.push(key.to_string());
+                for_leaves(item, path, f);
+                path.pop();
+            }
+        }
+        _ => f(path, value),
+    }
+}
+
+/// Visit every item of `new` that differs from the one in `old`, lists are visited by item.
+fn for_changed(new: &Value, old: Option<&Value>, path: &mut Vec<String>, f: &mut dyn FnMut(&[String])) {
+    match (new, old) {
+        (_, Some(old)) if new == old => (),
+        (Value::Dict(dict), Some(old @ Value::Dict(_))) => {
+            for (key, item) in dict.iter() {
+                path.push(key.to_string());
+                for_changed(item, lookup(old, &[key.to_string()]), path, f);
+                path.pop();
+            }
+        }
+        (Value::List(list), Some(old @ Value::List(_))) => {
+            for (index, item) in list.iter() {
+                path.push(index.to_string());
+                for_changed(item, lookup(old, &[index.to_string()]), path, f);
+                path.pop();
+            }
+        }
+        _ => f(path),
+    }
+}
+
+fn lookup<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+    let mut out = value;
+    for token in path {
+        out = match out {
+            Value::Dict(dict) => dict.get(token)?,
+            Value::List(list) => list.get(token)?,
+            _ => return None,
+        };
+    }
+    Some(out)
+}
+
+#[test]
+fn test_provenance() {
+    use crate::dict;
+    let file = |name: &str| Definition { file: Some(PathBuf::from(name)), range: None, layer: Layer::Document };
+    let mut base = Provenance::default();
+    let mut value = dict! {"a": 1, "b": dict! {"c": 2, "d": 3}};
+    base.define_all(&[], &value, &file("base.arc"));
+    assert_eq!(base.explain("b.c").len(), 1);
+
+    let incoming = dict! {"b": dict! {"c": 4, "d": 3}, "e": 5};
+    let mut source = Provenance::default();
+    source.define_all(&[], &incoming, &file("prod.arc"));
+    let before = value.clone();
+    value.merge(incoming.clone());
+    let layer = Definition { file: Some(PathBuf::from("prod.arc")), range: None, layer: Layer::Extend(PathBuf::from("prod.arc")) };
+    base.merge_layer(&[], &before, &value, &incoming, &source, &layer);
+    let chain = base.explain("b.c");
+    assert_eq!(chain.len(), 2);
+    assert_eq!(chain[1].to_string(), "prod.arc (extend prod.arc)");
+    // an equal value counts as set
+    assert_eq!(base.explain("b.d").len(), 2);
+    assert_eq!(base.explain("a").len(), 1);
+    assert_eq!(base.get("/e").unwrap().layer, Layer::Extend(PathBuf::from("prod.arc")));
+    assert!(base.explain("x").is_empty());
+}
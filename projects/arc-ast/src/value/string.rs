@@ -35,7 +35,7 @@ impl Display for Text {
             }
             TextDelimiter::Quotation(n) => {
                 write!(f, "{}", "\"".repeat(n))?;
-                write!(f, "{}", self.value)?;
+                write!(f, "{}", escape(&self.value, n > 1))?;
                 write!(f, "{}", "\"".repeat(n))?;
             }
             TextDelimiter::Apostrophe(n) => {
@@ -110,7 +110,145 @@ impl Text {
     pub fn get_handler(&self) -> Option<String> {
         self.handler.to_owned()
     }
+    pub fn take_handler(&mut self) -> Option<String> {
+        self.handler.take()
+    }
     pub fn as_str(&self) -> &str {
         self.value.as_str()
     }
 }
+
+/// Inverse of [`unescape`], newlines are kept in multiline strings.
+pub(crate) fn escape(text: &str, multiline: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\n' if multiline => out.push('\n'),
+            '"' if multiline => out.push('"'),
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\u{0008}' => out.push_str("\\b"),
+            '\u{000C}' => out.push_str("\\f"),
+            _ if c.is_control() => out.push_str(&format!("\\u{{{:X}}}", c as u32)),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Decode one escape sequence of a `"` string, such as `\n`, `\u00E9` or `\u{1F600}`.
+///
+/// `\u{...}` may hold several code points separated by spaces, surrogate code points are rejected.
+pub fn unescape(escape: &str) -> Result<String> {
+    let invalid = |message: String| Err(RuntimeError::syntax_error(ErrorCode::InvalidEscape, message, None));
+    let c = match escape {
+        "\\b" => '\u{0008}',
+        "\\t" => '\t',
+        "\\n" => '\n',
+        "\\f" => '\u{000C}',
+        "\\r" => '\r',
+        "\\\"" => '"',
+        "\\/" => '/',
+        "\\\\" => '\\',
+        // line continuation, the indentation of the next line is removed by `unescape_all`
+        "\\\n" => return Ok(String::new()),
+        _ => {
+            if let Some(codes) = escape.strip_prefix("\\u{").and_then(|s| s.strip_suffix('}')) {
+                let mut out = String::new();
+                for code in codes.split_whitespace() {
+                    out.push(decode_code_point(code)?)
+                }
+                return match out.is_empty() {
+                    true => invalid(format!("empty unicode escape `{}`", escape)),
+                    false => Ok(out),
+                };
+            }
+            match escape.strip_prefix("\\u") {
+                Some(code) if code.len() == 4 => decode_code_point(code)?,
+                _ => return invalid(format!("invalid escape `{}`", escape)),
+            }
+        }
+    };
+    Ok(c.to_string())
+}
+
+/// Decode all escape sequences of a `"` string, a backslash at the end of a line joins the next line.
+pub fn unescape_all(raw: &str) -> Result<String> {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find('\\') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let end = match tail.char_indices().nth(1) {
+            Some((_, 'u')) if tail[2..].starts_with('{') => tail.find('}').map_or(tail.len(), |i| i + 1),
+            Some((_, 'u')) => tail.char_indices().nth(6).map_or(tail.len(), |(i, _)| i),
+            Some((i, c)) => i + c.len_utf8(),
+            None => tail.len(),
+        };
+        out.push_str(&unescape(&tail[..end])?);
+        rest = &tail[end..];
+        if tail[..end].ends_with('\n') {
+            rest = rest.trim_start_matches([' ', '\t'])
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Layout of multiline strings.
+///
+/// The newline after the opening delimiter is dropped. If the closing delimiter is on its own line,
+/// that line is dropped too, and its indentation is removed from every line.
+pub fn dedent(raw: &str) -> String {
+    let raw = raw.strip_prefix('\n').unwrap_or(raw);
+    let (body, indent) = match raw.rfind('\n') {
+        Some(i) if raw[i + 1..].chars().all(|c| c == ' ') => (&raw[..i], raw.len() - i - 1),
+        _ => (raw, 0),
+    };
+    let lines: Vec<&str> = body
+        .split('\n')
+        .map(|line| {
+            let spaces = line.len() - line.trim_start_matches(' ').len();
+            &line[spaces.min(indent)..]
+        })
+        .collect();
+    lines.join("\n")
+}
+
+fn decode_code_point(hex: &str) -> Result<char> {
+    let code = u32::from_str_radix(hex, 16).ok();
+    match code.and_then(char::from_u32) {
+        Some(c) => Ok(c),
+        None => {
+            let message = match code {
+                Some(0xD800..=0xDFFF) => format!("surrogate code point `{}` is not a char", hex),
+                _ => format!("`{}` is not a valid code point", hex),
+            };
+            Err(RuntimeError::syntax_error(ErrorCode::InvalidEscape, message, None))
+        }
+    }
+}
+
+#[test]
+fn test_unescape() {
+    assert_eq!(unescape("\\t").unwrap(), "\t");
+    assert_eq!(unescape("\\\"").unwrap(), "\"");
+    assert_eq!(unescape("\\u00E9").unwrap(), "é");
+    assert_eq!(unescape("\\u{1F600}").unwrap(), "😀");
+    assert_eq!(unescape("\\u{48 49}").unwrap(), "HI");
+    assert!(unescape("\\q").is_err());
+    assert!(unescape("\\uD800").is_err());
+    assert!(unescape("\\u{110000}").is_err());
+    assert_eq!(unescape_all("a\\tb \\\n    c\\u0041").unwrap(), "a\tb cA");
+}
+
+#[test]
+fn test_dedent() {
+    assert_eq!(dedent("\n    SELECT *\n      FROM t\n    "), "SELECT *\n  FROM t");
+    assert_eq!(dedent("\n  a\nb\n  "), "a\nb");
+    assert_eq!(dedent("one line"), "one line");
+    assert_eq!(dedent("\n  keep\n  indent"), "  keep\n  indent");
+}
@@ -0,0 +1,171 @@
+use super::*;
+use num::{Integer as _, ToPrimitive, Zero};
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum UnitKind {
+    Duration,
+    DecimalByte,
+    BinaryByte,
+}
+
+/// Durations in nanoseconds, from small to large.
+static DURATION_UNITS: &[(&str, u64)] = &[
+    ("ns", 1),
+    ("us", 1_000),
+    ("ms", 1_000_000),
+    ("s", 1_000_000_000),
+    ("m", 60_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("d", 86_400_000_000_000),
+];
+/// SI byte sizes in bytes, from small to large.
+static DECIMAL_BYTE_UNITS: &[(&str, u64)] =
+    &[("B", 1), ("KB", 1_000), ("MB", 1_000_000), ("GB", 1_000_000_000), ("TB", 1_000_000_000_000), ("PB", 1_000_000_000_000_000)];
+/// IEC byte sizes in bytes, from small to large.
+static BINARY_BYTE_UNITS: &[(&str, u64)] = &[("B", 1), ("KiB", 1 << 10), ("MiB", 1 << 20), ("GiB", 1 << 30), ("TiB", 1 << 40), ("PiB", 1 << 50)];
+
+impl UnitKind {
+    fn units(self) -> &'static [(&'static str, u64)] {
+        match self {
+            UnitKind::Duration => DURATION_UNITS,
+            UnitKind::DecimalByte => DECIMAL_BYTE_UNITS,
+            UnitKind::BinaryByte => BINARY_BYTE_UNITS,
+        }
+    }
+    fn find(unit: &str) -> Option<(UnitKind, &'static str, u64)> {
+        let unit = match unit {
+            "µs" | "μs" => "us",
+            "kB" => "KB",
+            _ => unit,
+        };
+        for kind in &[UnitKind::Duration, UnitKind::DecimalByte, UnitKind::BinaryByte] {
+            if let Some((name, scale)) = kind.units().iter().find(|(name, _)| *name == unit) {
+                return Some((*kind, name, *scale));
+            }
+        }
+        None
+    }
+    /// The largest unit of the family that keeps `total` whole, from units up to `limit`.
+    fn largest(self, total: &BigInt, limit: u64) -> (&'static str, u64) {
+        let mut units = self.units().iter().rev().filter(|(_, scale)| *scale <= limit);
+        *units.find(|(_, scale)| (total % *scale).is_zero()).unwrap_or(&self.units()[0])
+    }
+}
+
+/// Read a number with a unit suffix, such as `30s`, `1h30m`, `250ms`, `10MB` or `512MiB`.
+///
+/// The amount keeps the smallest unit written, so `1000ms` stays `1000ms` and `1h30m` becomes `90m`. A fraction is
+/// rewritten in a smaller unit that keeps it whole, so `1.5h` becomes `90m`. Returns `None` if the suffix is not made
+/// of units, it stays a plain handler then.
+pub fn parse_unit(number: &str, suffix: &str) -> Option<Result<Integer>> {
+    let mut amount = match parse_number(number)? {
+        Value::Integer(n) => BigDecimal::new(n.get_value(), 0),
+        Value::Decimal(n) if n.is_finite() => n.get_value(),
+        _ => return None,
+    };
+    // `1h30m15s` is read as `1h`, `30m`, `15s`
+    let mut segments = vec![];
+    let mut rest = suffix;
+    loop {
+        let end = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let (kind, name, scale) = UnitKind::find(&rest[..end])?;
+        segments.push((amount, kind, name, scale));
+        rest = &rest[end..];
+        if rest.is_empty() {
+            break;
+        }
+        let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        amount = BigDecimal::from_str(&rest[..end]).ok()?;
+        rest = &rest[end..];
+        if rest.is_empty() {
+            return None;
+        }
+    }
+    let mut kind = segments[0].1;
+    let mut total = BigDecimal::zero();
+    for (amount, k, _, scale) in &segments {
+        match (kind, *k) {
+            (UnitKind::Duration, UnitKind::Duration) => (),
+            (UnitKind::Duration, _) | (_, UnitKind::Duration) => return None,
+            (_, UnitKind::BinaryByte) => kind = UnitKind::BinaryByte,
+            _ => (),
+        }
+        total += amount * BigDecimal::from(*scale);
+    }
+    if !total.is_integer() {
+        let message = format!("`{}{}` is not a whole number of {}", number, suffix, kind.units()[0].0);
+        return Some(Err(RuntimeError::syntax_error(ErrorCode::InvalidNumber, message, None)));
+    }
+    let (total, _) = total.with_scale(0).into_bigint_and_exponent();
+    let smallest = segments.iter().min_by_key(|(_, _, _, scale)| *scale).unwrap();
+    let (name, scale) = match total.is_zero() {
+        true => (smallest.2, smallest.3),
+        false => kind.largest(&total, smallest.3),
+    };
+    let mut out = Integer::from(total / scale);
+    out.set_handler(name);
+    Some(Ok(out))
+}
+
+impl Integer {
+    /// The value as a duration if the handler is a time unit, `None` if it is negative or too large.
+    pub fn as_duration(&self) -> Option<Duration> {
+        let nanos = match UnitKind::find(&self.get_handler()?)? {
+            (UnitKind::Duration, _, scale) => self.get_value() * scale,
+            _ => return None,
+        };
+        let (secs, nanos) = nanos.div_rem(&BigInt::from(1_000_000_000));
+        Some(Duration::new(secs.to_u64()?, nanos.to_u32()?))
+    }
+    /// The same amount in the largest unit of its family that keeps it whole, `1000ms` becomes `1s`.
+    ///
+    /// Returns `None` if the handler is not a unit.
+    pub fn normalize_unit(&self) -> Option<Integer> {
+        let (kind, _, scale) = UnitKind::find(&self.get_handler()?)?;
+        let total = self.get_value() * scale;
+        let (name, scale) = match total.is_zero() {
+            true => return Some(self.clone()),
+            false => kind.largest(&total, u64::MAX),
+        };
+        let mut out = Integer::from(total / scale);
+        out.set_handler(name);
+        Some(out)
+    }
+    /// The value in bytes if the handler is a byte unit, `None` if it is negative or too large.
+    pub fn as_byte_size(&self) -> Option<u64> {
+        match UnitKind::find(&self.get_handler()?)? {
+            (UnitKind::Duration, ..) => None,
+            (_, _, scale) => (self.get_value() * scale).to_u64(),
+        }
+    }
+}
+
+#[test]
+fn test_unit() {
+    let check = |number: &str, suffix: &str, out: &str| assert_eq!(parse_unit(number, suffix).unwrap().unwrap().to_string(), out);
+    check("30", "s", "30s");
+    check("1", "h30m", "90m");
+    check("1.5", "h", "90m");
+    check("250", "ms", "250ms");
+    check("1000", "ms", "1000ms");
+    check("10", "MB", "10MB");
+    check("1.5", "GB", "1500MB");
+    check("2048", "KiB", "2048KiB");
+    check("1", "GiB512MiB", "1536MiB");
+    check("0", "s", "0s");
+    let normalize = |number: &str, suffix: &str| parse_unit(number, suffix).unwrap().unwrap().normalize_unit().unwrap().to_string();
+    assert_eq!(normalize("1000", "ms"), "1s");
+    assert_eq!(normalize("2048", "KiB"), "2MiB");
+    assert_eq!(normalize("90", "m"), "90m");
+    assert!(parse_unit("1", "h30").is_none());
+    assert!(parse_unit("1", "h10MB").is_none());
+    assert!(parse_unit("1", "px").is_none());
+    assert!(parse_unit("1.5", "ns").unwrap().is_err());
+    let v = parse_unit("1", "h30m").unwrap().unwrap();
+    assert_eq!(v.as_duration(), Some(Duration::from_secs(5400)));
+    assert_eq!(v.as_byte_size(), None);
+    let v = parse_unit("512", "MiB").unwrap().unwrap();
+    assert_eq!(v.as_byte_size(), Some(512 << 20));
+    assert_eq!(parse_unit("-1", "s").unwrap().unwrap().as_duration(), None);
+}
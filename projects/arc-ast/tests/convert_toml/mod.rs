@@ -16,5 +16,7 @@ fn test_hard() -> Result<()> {
     test_toml("example")?;
     test_toml("hard")?;
     test_toml("hard_unicode")?;
+    test_toml("float_special")?;
+    test_toml("datetime")?;
     Ok(())
 }
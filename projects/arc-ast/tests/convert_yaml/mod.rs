@@ -26,6 +26,7 @@ fn test_normal() -> Result<()> {
     test_yaml("normal_3")?;
     test_yaml("normal_4")?;
     test_yaml("normal_5")?;
+    test_yaml("float_special")?;
     Ok(())
 }
 
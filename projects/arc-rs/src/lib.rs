@@ -1,9 +1,13 @@
 #![feature(box_syntax)]
 // mod ast;
 
+mod loader;
 mod parser;
 pub mod utils;
 
-pub use arc_ast::{ast, value, Value, AST};
-pub use arc_ast::{RuntimeError, Result};
+pub use arc_ast::serde::{from_value, to_string, to_string_pretty, to_value, to_writer, to_writer_pretty};
+pub use arc_ast::{ast, dict, list, value, Value, AST};
+pub use arc_ast::{Diagnostic, ErrorCode, Result, RuntimeError};
+pub use loader::Loader;
 pub use parser::ParserConfig;
+pub use utils::from_str;
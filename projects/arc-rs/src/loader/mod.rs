@@ -0,0 +1,251 @@
+use crate::{ErrorCode, ParserConfig, Result, RuntimeError, Value};
+use arc_ast::{
+    ast::{ASTKind, ExtendStatement},
+    value::{parse_path, Definition, Dict, Layer, MergePolicy, Provenance},
+    AST,
+};
+use std::{convert::TryFrom, env, path::PathBuf};
+
+/// Build one configuration from layered sources, later layers override earlier ones.
+///
+/// ```no_run
+/// use arc_rs::{dict, Loader};
+/// let config = Loader::new()
+///     .defaults(dict! {"port": 8080})
+///     .file("config/base.arc")
+///     .optional_file("config/local.toml")
+///     .env("APP")
+///     .overrides(std::env::args().skip(1))
+///     .load();
+/// ```
+#[derive(Default)]
+pub struct Loader {
+    parser: ParserConfig,
+    policy: MergePolicy,
+    layers: Vec<Source>,
+}
+
+enum Source {
+    Value(Value),
+    File { path: PathBuf, required: bool },
+    Env { prefix: String, vars: Option<Vec<(String, String)>> },
+    Override(String),
+}
+
+impl Loader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Parser used for arc files, its handlers also apply to environment variables and overrides.
+    pub fn parser(mut self, parser: ParserConfig) -> Self {
+        self.parser = parser;
+        self
+    }
+    /// Strategies used to merge each layer onto the layers before it.
+    pub fn policy(mut self, policy: MergePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+    /// In-code values, usually the defaults built with `dict!`.
+    pub fn defaults(mut self, value: impl Into<Value>) -> Self {
+        self.layers.push(Source::Value(value.into()));
+        self
+    }
+    /// A file in arc, json, toml or yaml, the format comes from the extension.
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.layers.push(Source::File { path: path.into(), required: true });
+        self
+    }
+    /// Same as [`Loader::file`], but a missing file is skipped.
+    pub fn optional_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.layers.push(Source::File { path: path.into(), required: false });
+        self
+    }
+    /// Environment variables of the process that start with `prefix`, read when loading.
+    ///
+    /// `APP__DATABASE__PORT=5432` with prefix `APP` sets `database.port`, segments are split on `__` and lowercased.
+    /// The path is followed as in [`Loader::set`], so `APP__SERVERS__0__HOST` sets the host of the first server,
+    /// and the value is merged there with the [`Loader::policy`]. Values are read as in [`Loader::set`].
+    ///
+    /// Variables whose name or value is not unicode are skipped.
+    pub fn env(mut self, prefix: &str) -> Self {
+        self.layers.push(Source::Env { prefix: prefix.to_string(), vars: None });
+        self
+    }
+    /// Same as [`Loader::env`], but reads the given variables instead of the process environment.
+    pub fn env_vars<I, K, V>(mut self, prefix: &str, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let vars = vars.into_iter().map(|(k, v)| (k.into(), v.into())).collect();
+        self.layers.push(Source::Env { prefix: prefix.to_string(), vars: Some(vars) });
+        self
+    }
+    /// One `key.path=value` override, the path is dotted or a JSON pointer and the value is read with arc syntax.
+    ///
+    /// The value replaces the one at the path without merging, so `features.1=trace` sets a single list item.
+    /// A value is kept as string if it does not parse, refers to other keys such as `$ecret`, reads as null without
+    /// being `null` or is a number with leading zeros such as `01234`.
+    pub fn set(mut self, assignment: impl Into<String>) -> Self {
+        self.layers.push(Source::Override(assignment.into()));
+        self
+    }
+    /// Overrides from command line arguments, see [`Loader::set`].
+    pub fn overrides<I>(mut self, args: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.layers.extend(args.into_iter().map(|arg| Source::Override(arg.into())));
+        self
+    }
+
+    /// Read all layers and merge them in order.
+    pub fn load(&self) -> Result<Value> {
+        let mut out = Dict::empty();
+        self.load_layers(&mut out, None)?;
+        Ok(out)
+    }
+    /// Same as [`Loader::load`], and record which layer set each value, see [`Provenance::explain`].
+    pub fn load_explained(&self) -> Result<(Value, Provenance)> {
+        let mut out = Dict::empty();
+        let mut provenance = Provenance::default();
+        self.load_layers(&mut out, Some(&mut provenance))?;
+        Ok((out, provenance))
+    }
+
+    fn load_layers(&self, out: &mut Value, mut provenance: Option<&mut Provenance>) -> Result<()> {
+        for layer in &self.layers {
+            match layer {
+                Source::Value(value) => {
+                    let definition = Definition { file: None, range: None, layer: Layer::Defaults };
+                    self.merge(out, value.clone(), None, definition, provenance.as_deref_mut())
+                }
+                Source::File { path, required } => {
+                    if !required && !path.exists() {
+                        continue;
+                    }
+                    let mut source = ExtendStatement::new(String::new(), path.to_string_lossy().to_string(), None);
+                    let mut parser = self.parser.child(path.to_path_buf())?;
+                    parser.explain = provenance.is_some();
+                    source.load(&parser)?;
+                    let definition = Definition { file: Some(path.clone()), range: None, layer: Layer::File(path.clone()) };
+                    self.merge(out, source.get_value().clone(), Some(source.get_provenance()), definition, provenance.as_deref_mut())
+                }
+                Source::Env { prefix, vars } => {
+                    let vars = match vars {
+                        Some(s) => s.clone(),
+                        // `env::vars` panics on a variable that is not unicode, even one without the prefix
+                        None => env::vars_os().filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?))).collect(),
+                    };
+                    for (key, value) in vars {
+                        if let Some((path, value)) = self.load_env(prefix, &key, &value) {
+                            let definition = Definition { file: None, range: None, layer: Layer::Env(key) };
+                            self.merge_at(out, &path, value, definition, provenance.as_deref_mut())?
+                        }
+                    }
+                }
+                Source::Override(assignment) => {
+                    let (path, value) = self.load_override(assignment)?;
+                    if let Some(provenance) = provenance.as_deref_mut() {
+                        let definition = Definition { file: None, range: None, layer: Layer::Override(assignment.clone()) };
+                        provenance.define_all(&parse_path(path)?, &value, &definition)
+                    }
+                    out.set_path(path, value)?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Merge one layer, `source` is where its values came from if known.
+    fn merge(&self, out: &mut Value, incoming: Value, source: Option<&Provenance>, definition: Definition, provenance: Option<&mut Provenance>) {
+        let provenance = match provenance {
+            Some(s) => s,
+            None => return out.merge_with(incoming, &self.policy),
+        };
+        let before = out.clone();
+        out.merge_with(incoming.clone(), &self.policy);
+        let empty = Provenance::default();
+        provenance.merge_layer(&[], &before, out, &incoming, source.unwrap_or(&empty), &definition)
+    }
+
+    /// Merge one value at `path`, the missing parents are created as by [`Value::set_path`].
+    fn merge_at(&self, out: &mut Value, path: &[String], incoming: Value, definition: Definition, provenance: Option<&mut Provenance>) -> Result<()> {
+        let pointer: String = path.iter().map(|s| format!("/{}", s.replace('~', "~0").replace('/', "~1"))).collect();
+        if out.pointer(&pointer).is_none() {
+            if let Some(provenance) = provenance {
+                provenance.define_all(path, &incoming, &definition)
+            }
+            return out.set_path(&pointer, incoming);
+        }
+        // strategies of the paths under `path`, relative to it
+        let mut policy = self.policy.clone();
+        policy.paths.retain(|(p, _)| p.len() >= path.len() && p.iter().zip(path).all(|(p, s)| p == "*" || p == s));
+        for (p, _) in policy.paths.iter_mut() {
+            *p = p.split_off(path.len())
+        }
+        let target = out.pointer_mut(&pointer).unwrap();
+        match provenance {
+            Some(provenance) => {
+                let before = target.clone();
+                target.merge_with(incoming.clone(), &policy);
+                provenance.merge_layer(path, &before, target, &incoming, &Provenance::default(), &definition)
+            }
+            None => target.merge_with(incoming, &policy),
+        }
+        Ok(())
+    }
+
+    fn load_env(&self, prefix: &str, key: &str, value: &str) -> Option<(Vec<String>, Value)> {
+        let rest = key.strip_prefix(prefix)?.strip_prefix("__")?;
+        if rest.is_empty() || rest.split("__").any(|s| s.is_empty()) {
+            return None;
+        }
+        Some((rest.split("__").map(|s| s.to_lowercase()).collect(), self.parse_value(value)))
+    }
+
+    fn load_override<'a>(&self, assignment: &'a str) -> Result<(&'a str, Value)> {
+        let (path, value) = match assignment.find('=') {
+            Some(i) => (assignment[..i].trim(), &assignment[i + 1..]),
+            None => {
+                let msg = format!("override `{}` should be `key.path=value`", assignment);
+                return Err(RuntimeError::structure_error(ErrorCode::InvalidKey, msg));
+            }
+        };
+        Ok((path, self.parse_value(value)))
+    }
+
+    /// Read a single value as the right side of an arc pair, or as string, see [`Loader::set`].
+    fn parse_value(&self, text: &str) -> Value {
+        let text = text.trim();
+        self.parse_literal(text).unwrap_or_else(|| Value::from(text))
+    }
+    fn parse_literal(&self, text: &str) -> Option<Value> {
+        let digits = text.trim_start_matches(&['+', '-'][..]).as_bytes();
+        if text.is_empty() || text.contains('\n') || (digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit()) {
+            return None;
+        }
+        let ast = self.parser.parse(&format!("value = {}", text)).ok()?;
+        if has_cite(&ast) {
+            return None;
+        }
+        match Value::try_from(ast).ok()?.pointer("value") {
+            Some(Value::Null) if text != "null" => None,
+            Some(s) => Some(s.clone()),
+            None => None,
+        }
+    }
+}
+
+/// Whether a value refers to other keys, such as `$secret`.
+fn has_cite(ast: &AST) -> bool {
+    match &ast.kind {
+        ASTKind::Cite(_) | ASTKind::CiteQuery(_) => true,
+        ASTKind::Program(v) | ASTKind::Sequence(v) | ASTKind::Dict(v) | ASTKind::List(v) => v.iter().any(has_cite),
+        ASTKind::Pair(_, v) | ASTKind::Handler(_, v) => has_cite(v),
+        _ => false,
+    }
+}
@@ -1,18 +1,45 @@
 use super::*;
-use std::path::PathBuf;
+use arc_ast::{function::HandlerRegistry, traits::ExtendFormat, value::Provenance, Value};
+use std::{
+    convert::TryFrom,
+    fs::canonicalize,
+    path::{Path, PathBuf},
+};
 
 pub struct ParserConfig {
     pub tab_size: usize,
-    pub file_path: Option<PathBuf>
+    pub file_path: Option<PathBuf>,
+    /// Handlers for prefixed literals and number suffixes other than units and rust types, an unknown prefix or suffix
+    /// is kept as handler
+    pub handlers: HandlerRegistry,
+    /// Canonical paths of the files being loaded by `#extend` or `#import`, outermost first
+    pub loading: Vec<PathBuf>,
+    /// Record where the values of extended and imported files were defined, see [`ExtendFormat::parse_explained`]
+    pub explain: bool,
 }
 
 impl Default for ParserConfig {
     fn default() -> Self {
-        Self { tab_size: 4, file_path: None }
+        Self { tab_size: 4, file_path: None, handlers: HandlerRegistry::default(), loading: vec![], explain: false }
     }
 }
 
 impl ParserConfig {
+    /// Config for a file loaded by `#extend` or `#import`, fails if the file is already being loaded.
+    pub fn child(&self, file_path: PathBuf) -> Result<Self> {
+        let mut loading = self.loading.clone();
+        if loading.is_empty() {
+            loading.extend(self.file_path.as_deref().map(canonical));
+        }
+        let path = canonical(&file_path);
+        if let Some(start) = loading.iter().position(|p| *p == path) {
+            let chain: Vec<String> = loading[start..].iter().chain(Some(&path)).map(|p| p.display().to_string()).collect();
+            let message = format!("circular extend/import: {}", chain.join(" -> "));
+            return Err(RuntimeError::syntax_error(ErrorCode::CircularExtend, message, None));
+        }
+        loading.push(path);
+        Ok(Self { tab_size: self.tab_size, file_path: Some(file_path), handlers: self.handlers.clone(), loading, explain: self.explain })
+    }
     pub fn get_position(&self, s: Span) -> TextRange {
         let us = s.start_pos().line_col();
         let es = s.end_pos().line_col();
@@ -23,3 +50,22 @@ impl ParserConfig {
         }
     }
 }
+
+/// A file that does not exist yet keeps its path, reading it reports the error.
+fn canonical(path: &Path) -> PathBuf {
+    canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+impl ExtendFormat for ParserConfig {
+    fn parse(&self, input: &str) -> Result<Value> {
+        Value::try_from(ParserConfig::parse(self, input)?)
+    }
+    fn parse_explained(&self, input: &str) -> Result<(Value, Provenance)> {
+        // the files loaded while parsing must be explained too
+        let config = Self { tab_size: self.tab_size, file_path: self.file_path.clone(), handlers: self.handlers.clone(), loading: self.loading.clone(), explain: true };
+        Value::try_explained(ParserConfig::parse(&config, input)?, self.file_path.clone())
+    }
+    fn explain(&self) -> bool {
+        self.explain
+    }
+}
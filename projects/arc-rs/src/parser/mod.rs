@@ -1,63 +1,108 @@
 mod config;
+mod recover;
 pub use crate::parser::config::ParserConfig;
-use crate::{Result, RuntimeError};
+use crate::{ErrorCode, Result, RuntimeError};
 use arc_ast::{
     ast::ASTKind,
-    value::{Text},
-    TextRange, AST,
+    value::{dedent, is_type_suffix, parse_byte, parse_unit, unescape, unescape_all, DateTime, Decimal, Query, Text},
+    TextRange, Value, AST,
 };
-use arc_pest::{ArcParser, Pair, Pairs, Parser, Rule, Span};
-use crate::ast::ExtendStatement;
-
-macro_rules! debug_cases {
-    ($i:ident) => {{
-        println!("Rule::{:?}=>continue,", $i.as_rule());
-        println!("Span: {:?}", $i.as_span());
-        println!("Text: {}", $i.as_str());
-        unreachable!();
-    }};
-}
+use arc_pest::{pest::error::LineColLocation, ArcParser, Error, Pair, Pairs, Parser, Rule, Span};
+use crate::ast::{ExtendStatement, ImportStatement, MergeStatement};
+use std::{convert::TryFrom, str::FromStr};
 
 impl ParserConfig {
     pub fn parse(&self, input: &str) -> Result<AST> {
-        let input = input.replace("\r\n", "\n").replace("\\\n", "").replace("\t", &" ".repeat(self.tab_size));
+        let input = input.replace("\r\n", "\n").replace("\t", &" ".repeat(self.tab_size));
         match ArcParser::parse(Rule::program, &input) {
-            Ok(o) => Ok(self.parse_program(o)),
-            Err(e) => Err(RuntimeError::OtherError(box e))
+            Ok(o) => self.parse_program(o),
+            Err(e) => Err(self.syntax_error(e)),
         }
     }
-    fn parse_program(&self, pairs: Pairs<Rule>) -> AST {
+    /// The grammar accepted the input but produced a node that this parser has no case for.
+    fn unexpected(&self, pair: &Pair<Rule>) -> RuntimeError {
+        let message = format!("unexpected {:?}: `{}`", pair.as_rule(), pair.as_str());
+        RuntimeError::syntax_error(ErrorCode::UnexpectedRule, message, Some(self.get_position(pair.as_span())))
+    }
+    fn syntax_error(&self, e: Error<Rule>) -> RuntimeError {
+        let range = match e.line_col {
+            LineColLocation::Pos(s) => TextRange { start: (s.0 as u64, s.1 as u64), end: (s.0 as u64, s.1 as u64) },
+            LineColLocation::Span(s, e) => TextRange { start: (s.0 as u64, s.1 as u64), end: (e.0 as u64, e.1 as u64) },
+        };
+        RuntimeError::syntax_error(ErrorCode::InvalidSyntax, e.variant.message(), Some(range))
+    }
+    fn parse_program(&self, pairs: Pairs<Rule>) -> Result<AST> {
         let mut codes = vec![];
         let mut additional = None;
         for pair in pairs {
             match pair.as_rule() {
                 Rule::EOI => continue,
                 Rule::statement => {
-                    codes.push(self.parse_extend(pair));
+                    codes.push(self.parse_extend(pair)?);
                 }
                 Rule::data => return self.parse_data(pair),
-                Rule::dict_pair => codes.push(self.parse_dict_pair(pair)),
-                Rule::dict_head => codes.push(self.parse_dict_head(pair)),
+                Rule::dict_pair => codes.push(self.parse_dict_pair(pair)?),
+                Rule::dict_head => codes.push(self.parse_dict_head(pair)?),
+                Rule::list_head => codes.push(self.parse_list_head(pair)?),
+                Rule::list_pair => codes.push(self.parse_list_pair(pair)?),
                 Rule::COMMENT => additional = Some(pair.as_str().to_string()),
-                Rule::extend_statement=> codes.push(self.parse_extend(pair)),
-                _ => debug_cases!(pair),
+                Rule::extend_statement=> codes.push(self.parse_extend(pair)?),
+                Rule::import_statement => codes.push(self.parse_import(pair)?),
+                Rule::merge_statement => codes.push(self.parse_merge(pair)?),
+                _ => return Err(self.unexpected(&pair)),
             };
         }
-        AST { kind: ASTKind::Program(codes), range: None, additional }
+        Ok(AST { kind: ASTKind::Program(codes), range: None, additional })
     }
-    fn parse_extend(&self, pairs: Pair<Rule>) -> AST {
+    fn parse_extend(&self, pairs: Pair<Rule>) -> Result<AST> {
         let r = self.get_position(pairs.as_span());
         let mut path = String::new();
         let mut format= String::new();
         for pair in pairs.into_inner() {
              match pair.as_rule() {
                 Rule::SYMBOL=> format = pair.as_str().to_string(),
-                Rule::StringNormal=> path = self.parse_string_inner(pair).as_str().to_string(),
-                _ => debug_cases!(pair),
+                Rule::StringNormal=> path = self.parse_string_inner(pair)?.as_str().to_string(),
+                _ => return Err(self.unexpected(&pair)),
             };
         }
-        let ext = ExtendStatement::new(format, path, self.file_path.to_owned());
-        AST { kind: ASTKind::ExtendStatement(ext), range: r.boxed(), additional:None }
+        let mut ext = ExtendStatement::new(format, path, self.file_path.to_owned());
+        let child = self.child(ext.get_path().to_owned()).map_err(|e| e.with_range(Some(r)))?;
+        ext.load(&child)?;
+        Ok(AST { kind: ASTKind::ExtendStatement(ext), range: r.boxed(), additional:None })
+    }
+    fn parse_import(&self, pairs: Pair<Rule>) -> Result<AST> {
+        let r = self.get_position(pairs.as_span());
+        let mut path = String::new();
+        let mut format = String::new();
+        let mut namespace = AST::default();
+        for pair in pairs.into_inner() {
+            match pair.as_rule() {
+                Rule::SYMBOL => format = pair.as_str().to_string(),
+                Rule::StringNormal => path = self.parse_string_inner(pair)?.as_str().to_string(),
+                Rule::namespace => namespace = self.parse_namespace(pair)?,
+                _ => return Err(self.unexpected(&pair)),
+            };
+        }
+        let mut import = ImportStatement::new(format, path, self.file_path.to_owned(), namespace);
+        let child = self.child(import.get_source().get_path().to_owned()).map_err(|e| e.with_range(Some(r)))?;
+        import.load(&child)?;
+        Ok(AST { kind: ASTKind::ImportStatement(import), range: r.boxed(), additional: None })
+    }
+    fn parse_merge(&self, pairs: Pair<Rule>) -> Result<AST> {
+        let r = self.get_position(pairs.as_span());
+        let mut strategy = String::new();
+        let mut key = None;
+        let mut path = String::new();
+        for pair in pairs.into_inner() {
+            match pair.as_rule() {
+                Rule::SYMBOL if strategy.is_empty() => strategy = pair.as_str().to_string(),
+                Rule::SYMBOL => key = Some(pair.as_str().to_string()),
+                Rule::StringNormal => path = self.parse_string_inner(pair)?.as_str().to_string(),
+                Rule::StringEmpty => continue,
+                _ => return Err(self.unexpected(&pair)),
+            };
+        }
+        Ok(AST { kind: ASTKind::MergeStatement(MergeStatement::new(strategy, key, path)), range: r.boxed(), additional: None })
     }
     // fn parse_block(&self, pairs: Pair<Rule>) -> AST {
     //     let pair = pairs.into_inner().nth(0).unwrap();
@@ -69,7 +114,7 @@ impl ParserConfig {
 }
 
 impl ParserConfig {
-    fn parse_data(&self, pairs: Pair<Rule>) -> AST {
+    fn parse_data(&self, pairs: Pair<Rule>) -> Result<AST> {
         let pair = pairs.into_inner().nth(0).unwrap();
         match pair.as_rule() {
             Rule::list_literal => self.parse_list_literal(pair),
@@ -77,70 +122,140 @@ impl ParserConfig {
             Rule::String => self.parse_string(pair),
             Rule::Special => self.parse_special(pair),
             Rule::Number => self.parse_number(pair),
+            Rule::Byte => self.parse_byte(pair),
+            Rule::DateTime => self.parse_datetime(pair),
             Rule::Cite => self.parse_cite(pair),
             // Rule::Symbol => self.parse_namespace(pair),
             // Rule::SpecialValue => self.parse_special(pair),
-            _ => debug_cases!(pair),
+            _ => Err(self.unexpected(&pair)),
         }
     }
-    fn parse_list_literal(&self, pairs: Pair<Rule>) -> AST {
+    fn parse_list_literal(&self, pairs: Pair<Rule>) -> Result<AST> {
         let r = self.get_position(pairs.as_span());
+        let mut handler = None;
         let mut codes = vec![];
         for pair in pairs.into_inner() {
             match pair.as_rule() {
                 Rule::SEPARATOR => continue,
-                Rule::InlineString=>codes.push( self.parse_string_bare(pair)),
-                Rule::data => codes.push(self.parse_data(pair)),
-                _ => debug_cases!(pair),
+                Rule::SYMBOL => handler = Some(pair.as_str().to_string()),
+                Rule::InlineString=>codes.push( self.parse_string_bare(pair)?),
+                Rule::data => codes.push(self.parse_data(pair)?),
+                _ => return Err(self.unexpected(&pair)),
             };
         }
         let mut out = AST::list(codes);
         out.set_range(r);
-        return out;
+        match handler {
+            Some(handler) => self.apply_handler(handler, out),
+            None => Ok(out),
+        }
+    }
+    /// Run the registered handler on a prefixed literal, an unknown handler is kept on the value.
+    fn apply_handler(&self, handler: String, literal: AST) -> Result<AST> {
+        let AST { kind, range, additional } = literal;
+        if let Some(f) = self.handlers.get(&handler) {
+            let input = Value::try_from(AST::from(kind))?;
+            let output = match f.apply(&[input], &Default::default()) {
+                Ok(o) => o,
+                Err(e) if e.get_diagnostic().is_some() => return Err(e.with_range(range)),
+                Err(e) => {
+                    let message = format!("invalid `{}` literal: {}", handler, e);
+                    return Err(RuntimeError::syntax_error(ErrorCode::HandlerFailed, message, range));
+                }
+            };
+            return Ok(AST { kind: ASTKind::Value(Box::new(output)), range, additional });
+        }
+        let kind = match kind {
+            ASTKind::String(mut v) => {
+                v.set_handler(handler);
+                ASTKind::String(v)
+            }
+            _ => ASTKind::Handler(handler, Box::new(AST { kind, range, additional: None })),
+        };
+        Ok(AST { kind, range, additional })
     }
-    fn parse_dict_head(&self, pairs: Pair<Rule>) -> AST {
+    fn parse_list_head(&self, pairs: Pair<Rule>) -> Result<AST> {
         let r = self.get_position(pairs.as_span());
         let mut depth = 0;
         let mut path = AST::default();
         for pair in pairs.into_inner() {
             match pair.as_rule() {
                 Rule::Dot => depth += 1,
-                Rule::namespace => path = self.parse_namespace(pair),
-                _ => debug_cases!(pair),
+                Rule::namespace => path = self.parse_namespace(pair)?,
+                _ => return Err(self.unexpected(&pair)),
             };
         }
-        AST { kind: ASTKind::DictScope(depth, Box::new(path)), range: r.boxed(), additional: None }
+        Ok(AST { kind: ASTKind::ListScope(depth, Box::new(path)), range: r.boxed(), additional: None })
     }
-    fn parse_dict_literal(&self, pairs: Pair<Rule>) -> AST {
+    fn parse_list_pair(&self, pairs: Pair<Rule>) -> Result<AST> {
         let r = self.get_position(pairs.as_span());
+        let mut is_insert = false;
+        let mut codes = vec![];
+        for pair in pairs.clone().into_inner() {
+            match pair.as_rule() {
+                Rule::Insert => is_insert = true,
+                Rule::Append => is_insert = false,
+                Rule::dict_pair => codes.push(self.parse_dict_pair(pair)?),
+                Rule::data => codes.push(self.parse_data(pair)?),
+                Rule::InlineString => codes.push(self.parse_string_bare(pair)?),
+                _ => return Err(self.unexpected(&pair)),
+            };
+        }
+        let kind = match is_insert {
+            true if !codes.is_empty() => ASTKind::ListInsert(codes),
+            false if codes.len() == 1 => ASTKind::ListAppend(Box::new(codes.remove(0))),
+            _ => return Err(self.unexpected(&pairs)),
+        };
+        Ok(AST { kind, range: r.boxed(), additional: None })
+    }
+    fn parse_dict_head(&self, pairs: Pair<Rule>) -> Result<AST> {
+        let r = self.get_position(pairs.as_span());
+        let mut depth = 0;
+        let mut path = AST::default();
+        for pair in pairs.into_inner() {
+            match pair.as_rule() {
+                Rule::Dot => depth += 1,
+                Rule::namespace => path = self.parse_namespace(pair)?,
+                _ => return Err(self.unexpected(&pair)),
+            };
+        }
+        Ok(AST { kind: ASTKind::DictScope(depth, Box::new(path)), range: r.boxed(), additional: None })
+    }
+    fn parse_dict_literal(&self, pairs: Pair<Rule>) -> Result<AST> {
+        let r = self.get_position(pairs.as_span());
+        let mut handler = None;
         let mut codes = vec![];
         for pair in pairs.into_inner() {
             match pair.as_rule() {
                 Rule::SEPARATOR => continue,
-                Rule::dict_pair => codes.push(self.parse_dict_pair(pair)),
-                _ => debug_cases!(pair),
+                Rule::SYMBOL => handler = Some(pair.as_str().to_string()),
+                Rule::dict_pair => codes.push(self.parse_dict_pair(pair)?),
+                _ => return Err(self.unexpected(&pair)),
             };
         }
         let mut out = AST::dict(codes);
         out.set_range(r);
-        return out;
+        match handler {
+            Some(handler) => self.apply_handler(handler, out),
+            None => Ok(out),
+        }
     }
-    fn parse_dict_pair(&self, pairs: Pair<Rule>) -> AST {
+    fn parse_dict_pair(&self, pairs: Pair<Rule>) -> Result<AST> {
         let r = self.get_position(pairs.as_span());
         let mut key = AST::default();
         let mut value = AST::default();
         for pair in pairs.into_inner() {
             match pair.as_rule() {
                 Rule::Set => continue,
-                Rule::namespace => key = self.parse_namespace(pair),
-                Rule::data => value = self.parse_data(pair),
-                Rule::RestLineText => value = self.parse_string_bare(pair),
-                _ => debug_cases!(pair),
+                Rule::namespace => key = self.parse_namespace(pair)?,
+                Rule::data => value = self.parse_data(pair)?,
+                Rule::RestLineText => value = self.parse_string_bare(pair)?,
+                _ => return Err(self.unexpected(&pair)),
             };
         }
         let mut out = AST::pair(key, value);
         out.set_range(r);
-        return out;
+        Ok(out)
     }
     //
     // fn parse_list(&self, pairs: Pair<Rule>) -> AST {
@@ -168,14 +283,14 @@ impl ParserConfig {
     //     }
     //     (key, value)
     // }
-    fn parse_namespace(&self, pairs: Pair<Rule>) -> AST {
+    fn parse_namespace(&self, pairs: Pair<Rule>) -> Result<AST> {
         let r = self.get_position(pairs.as_span());
         let mut symbols: Vec<AST> = vec![];
         for pair in pairs.into_inner() {
             match pair.as_rule() {
                 Rule::Dot => continue,
                 Rule::StringNormal => {
-                    let key = AST::string(self.parse_string_inner(pair));
+                    let key = AST::string(self.parse_string_inner(pair)?);
                     symbols.push(key)
                 }
                 Rule::SYMBOL => {
@@ -186,12 +301,12 @@ impl ParserConfig {
                     let index = AST::integer(pair.as_str());
                     symbols.push(index)
                 }
-                _ => debug_cases!(pair),
+                _ => return Err(self.unexpected(&pair)),
             };
         }
         let mut out = AST::namespace(symbols);
         out.set_range(r);
-        return out;
+        Ok(out)
     }
     //
     // fn parse_string(&self, pairs: Pair<Rule>) -> AST {
@@ -238,66 +353,158 @@ impl ParserConfig {
     //     }
     // }
     //
-    fn parse_string(&self, pairs: Pair<Rule>) -> AST {
+    fn parse_string(&self, pairs: Pair<Rule>) -> Result<AST> {
         let r = self.get_position(pairs.as_span());
+        let mut handler = None;
         let mut text = Text::default();
         for pair in pairs.into_inner() {
             match pair.as_rule() {
-                Rule::StringNormal => text = self.parse_string_inner(pair),
-                _ => debug_cases!(pair),
+                Rule::StringNormal => text = self.parse_string_inner(pair)?,
+                Rule::SYMBOL => handler = Some(pair.as_str().to_string()),
+                Rule::StringEmpty => {
+                    text = match pair.as_str().starts_with('\'') {
+                        true => Text::string_literal("", "", 1),
+                        false => Text::string_escaped("", "", 1),
+                    }
+                }
+                _ => return Err(self.unexpected(&pair)),
             };
         }
         let mut out = AST::string(text);
         out.set_range(r);
-        return out;
+        match handler {
+            Some(handler) => self.apply_handler(handler, out),
+            None => Ok(out),
+        }
     }
-    fn parse_string_bare(&self, pairs: Pair<Rule>) -> AST {
+    fn parse_string_bare(&self, pairs: Pair<Rule>) -> Result<AST> {
         let r = self.get_position(pairs.as_span());
-        let text = Text::string_bare(pairs.as_str());
+        // a `\` at the end of a line joins the next line
+        let text = Text::string_bare(pairs.as_str().replace("\\\n", ""));
         let mut out = AST::string(text);
         out.set_range(r);
-        return out;
+        Ok(out)
     }
-    fn parse_string_inner(&self, pairs: Pair<Rule>) -> Text {
+    fn parse_string_inner(&self, pairs: Pair<Rule>) -> Result<Text> {
         let mut is_literal = false;
         let mut text = String::with_capacity(pairs.as_str().len());
         let mut delimiter = 0;
+        let mut in_text = false;
         for pair in pairs.into_inner() {
             match pair.as_rule() {
+                Rule::S1 | Rule::S2 if in_text => continue,
                 Rule::S1 => {
                     delimiter += 1;
                     is_literal = true
                 }
                 Rule::S2 => delimiter += 1,
-                Rule::NS1 | Rule::NS2 => text.push_str(pair.as_str()),
-                _ => debug_cases!(pair),
+                Rule::NS1 => {
+                    in_text = true;
+                    text.push_str(pair.as_str())
+                }
+                Rule::NS2 => {
+                    in_text = true;
+                    // check each escape here to report its position
+                    if pair.as_str().starts_with('\\') {
+                        let r = self.get_position(pair.as_span());
+                        unescape(pair.as_str()).map_err(|e| e.with_range(Some(r)))?;
+                    }
+                    text.push_str(pair.as_str())
+                }
+                _ => return Err(self.unexpected(&pair)),
             };
         }
-        match is_literal {
-            true => Text::string_literal(text, "", delimiter / 2),
-            false => Text::string_escaped(text, "", delimiter / 2),
+        if delimiter > 1 {
+            text = dedent(&text)
         }
+        let out = match is_literal {
+            true => Text::string_literal(text, "", delimiter),
+            false => Text::string_escaped(unescape_all(&text)?, "", delimiter),
+        };
+        Ok(out)
     }
-    fn parse_cite(&self, pairs: Pair<Rule>) -> AST {
+    fn parse_cite(&self, pairs: Pair<Rule>) -> Result<AST> {
         let r = self.get_position(pairs.as_span());
         let item = pairs.into_inner().next().unwrap();
-        AST { kind: ASTKind::Cite(Box::new(self.parse_namespace(item))), range: r.boxed(), additional: None }
+        let query = match item.as_rule() {
+            Rule::CiteQuery => {
+                let query = item.as_str();
+                query[1..query.len() - 1].trim().to_string()
+            }
+            // `$servers/*/pw` is `${servers.*.pw}`
+            Rule::CiteSlash => item.into_inner().map(|e| e.as_str()).collect::<Vec<_>>().join("."),
+            _ if item.clone().into_inner().any(|e| e.as_rule() == Rule::Star) => item.as_str().to_string(),
+            _ => return Ok(AST { kind: ASTKind::Cite(Box::new(self.parse_namespace(item)?)), range: r.boxed(), additional: None }),
+        };
+        if let Err(e) = Query::from_str(&query) {
+            return Err(RuntimeError::syntax_error(ErrorCode::InvalidSyntax, e.into_diagnostic().message, Some(r)));
+        }
+        Ok(AST { kind: ASTKind::CiteQuery(query), range: r.boxed(), additional: None })
     }
-    fn parse_number(&self, pairs: Pair<Rule>) -> AST {
+    fn parse_number(&self, pairs: Pair<Rule>) -> Result<AST> {
         let r = self.get_position(pairs.as_span());
         let mut items = pairs.into_inner();
-        let mut out = AST::number(items.next().unwrap().as_str());
+        let number = items.next().unwrap().as_str();
+        let mut out = AST::number(number);
+        if let Some(suffix) = items.next() {
+            // a registered handler gets the number without its suffix, units and rust types are built in
+            let unit = parse_unit(number, suffix.as_str());
+            if unit.is_none() && !is_type_suffix(suffix.as_str()) && self.handlers.get(suffix.as_str()).is_some() {
+                out.set_range(r);
+                return self.apply_handler(suffix.as_str().to_string(), out);
+            }
+            match (unit, &mut out.kind) {
+                (Some(unit), _) => out.kind = ASTKind::Integer(Box::new(unit.map_err(|e| e.with_range(Some(r)))?)),
+                (None, ASTKind::Integer(n)) => n.set_handler(suffix.as_str()),
+                (None, ASTKind::Decimal(n)) => n.set_handler(suffix.as_str()),
+                _ => (),
+            }
+        }
+        self.check_number(&out, r)?;
         out.set_range(r);
-        return out;
+        Ok(out)
+    }
+    /// Literals with a rust type suffix must fit that type.
+    fn check_number(&self, number: &AST, r: TextRange) -> Result<()> {
+        let checked = match &number.kind {
+            ASTKind::Integer(n) => n.check_handler(),
+            ASTKind::Decimal(n) => n.check_handler(),
+            _ => Ok(()),
+        };
+        checked.map_err(|e| e.with_range(Some(r)))
+    }
+    fn parse_byte(&self, pairs: Pair<Rule>) -> Result<AST> {
+        let r = self.get_position(pairs.as_span());
+        match parse_byte(pairs.as_str()) {
+            Some(n) => {
+                let out = AST { kind: ASTKind::Integer(Box::new(n)), range: r.boxed(), additional: None };
+                self.check_number(&out, r)?;
+                Ok(out)
+            }
+            None => {
+                let message = format!("invalid byte literal `{}`", pairs.as_str());
+                Err(RuntimeError::syntax_error(ErrorCode::InvalidNumber, message, Some(r)))
+            }
+        }
+    }
+    fn parse_datetime(&self, pairs: Pair<Rule>) -> Result<AST> {
+        let r = self.get_position(pairs.as_span());
+        let value = DateTime::from_str(pairs.as_str()).map_err(|e| e.with_range(Some(r)))?;
+        Ok(AST { kind: ASTKind::DateTime(Box::new(value)), range: r.boxed(), additional: None })
     }
-    fn parse_special(&self, pairs: Pair<Rule>) -> AST {
+    fn parse_special(&self, pairs: Pair<Rule>) -> Result<AST> {
         let r = self.get_position(pairs.as_span());
         let mut out = match pairs.as_str() {
             "true" => AST::boolean(true),
             "false" => AST::boolean(false),
-            _ => AST::null(),
+            "null" => AST::null(),
+            // `inf`, `-Infinity`, `NaN`
+            s => match s.parse::<f64>() {
+                Ok(o) => AST::from(ASTKind::Decimal(Box::new(Decimal::from(o)))),
+                Err(_) => return Err(self.unexpected(&pairs)),
+            },
         };
         out.set_range(r);
-        return out;
+        Ok(out)
     }
 }
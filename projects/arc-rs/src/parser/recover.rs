@@ -0,0 +1,179 @@
+use super::*;
+use arc_ast::Diagnostic;
+
+impl ParserConfig {
+    /// Parse as much of the input as possible, for editors and linters.
+    ///
+    /// When a statement is broken, the parser reports it and resynchronises at the next statement boundary,
+    /// a `{dict_head}`, a `[list_head]`, a `#` statement, a list item or a line starting with `key =`.
+    /// Lines inside multiline strings, comments and bracketed values are never boundaries.
+    ///
+    /// The statements after a broken `{dict_head}` or `[list_head]` are dropped up to the next head, so that they are
+    /// not moved into the scope before it.
+    pub fn parse_recover(&self, input: &str) -> (AST, Vec<Diagnostic>) {
+        let input = input.replace("\r\n", "\n");
+        let lines: Vec<&str> = input.split('\n').collect();
+        let starts = statement_starts(&lines);
+        let mut codes = vec![];
+        let mut diagnostics = vec![];
+        let mut first = 0;
+        loop {
+            let mut end = lines.len();
+            let mut error = None;
+            // retry with the statements before the broken one, the error of the shortest failing range is kept
+            let parsed = loop {
+                match self.parse_lines(&lines, first, end) {
+                    Ok(o) => break Some(o),
+                    Err(e) => {
+                        let diagnostic = e.into_diagnostic();
+                        // lines are 1-indexed in diagnostics
+                        let line = diagnostic.range.as_ref().map_or(first, |r| (r.start.0 as usize).saturating_sub(1));
+                        let broken = starts.iter().rev().copied().find(|&s| s >= first && s < end && s <= line).unwrap_or(first);
+                        error = Some((broken, diagnostic));
+                        if broken == first {
+                            break None;
+                        }
+                        end = broken
+                    }
+                }
+            };
+            match parsed {
+                Some(o) if first == 0 && error.is_none() => return (o, diagnostics),
+                Some(AST { kind: ASTKind::Program(v), .. }) => codes.extend(v),
+                Some(o) => codes.push(o),
+                None => (),
+            }
+            let (broken, mut diagnostic) = match error {
+                Some(s) => s,
+                None => break,
+            };
+            if diagnostic.range.is_none() {
+                let line = broken as u64 + 1;
+                diagnostic.range = Some(TextRange::new(line, 1u64, line, 1u64))
+            }
+            diagnostics.push(diagnostic);
+            let head = is_head(lines[broken]);
+            match starts.iter().find(|&&s| s > broken && (!head || is_head(lines[s]))) {
+                Some(s) => first = *s,
+                None => break,
+            }
+        }
+        (AST { kind: ASTKind::Program(codes), range: None, additional: None }, diagnostics)
+    }
+
+    /// Parse `lines[first..end]` and keep the line numbers of the whole input.
+    fn parse_lines(&self, lines: &[&str], first: usize, end: usize) -> Result<AST> {
+        self.parse(&format!("{}{}", "\n".repeat(first), lines[first..end].join("\n")))
+    }
+}
+
+/// Returns the 0-indexed first line of each statement.
+fn statement_starts(lines: &[&str]) -> Vec<usize> {
+    let mut out = vec![];
+    let mut scan = Scan::default();
+    for (index, line) in lines.iter().enumerate() {
+        // items of a multiline value are indented, so an unclosed bracket ends at a statement in the first column
+        if !line.starts_with(char::is_whitespace) && is_statement_start(line) {
+            scan.brackets = 0
+        }
+        let top = scan.is_top();
+        if index == 0 || (top && is_statement_start(line)) {
+            out.push(index)
+        }
+        // a head is one line, an unclosed one must not hide the next statements
+        let head = top && is_head(line);
+        scan.line(line, head)
+    }
+    out
+}
+
+fn is_head(line: &str) -> bool {
+    line.trim_start().starts_with(&['{', '['][..])
+}
+
+fn is_statement_start(line: &str) -> bool {
+    let line = line.trim_start();
+    match line.chars().next() {
+        Some('{') | Some('[') | Some('#') | Some('^') | Some('>') => true,
+        Some(c) if c.is_alphanumeric() || c == '_' || c == '-' || c == '"' || c == '\'' => line.contains(&['=', ':'][..]),
+        _ => false,
+    }
+}
+
+/// What is still open at the end of a line.
+#[derive(Default)]
+struct Scan {
+    /// The quote and the delimiter count of a multiline string
+    string: Option<(char, usize)>,
+    /// Depth of nested `/* */` comments
+    comment: usize,
+    /// Depth of `[` and `{` in values
+    brackets: usize,
+    /// The line ends with a `\` continuation
+    continued: bool,
+}
+
+impl Scan {
+    fn is_top(&self) -> bool {
+        self.string.is_none() && self.comment == 0 && self.brackets == 0 && !self.continued
+    }
+    fn line(&mut self, line: &str, head: bool) {
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            if let Some((quote, count)) = self.string {
+                if c == '\\' && quote == '"' {
+                    i += 2;
+                    continue;
+                }
+                let run = chars[i..].iter().take_while(|&&e| e == quote).count();
+                if run >= count {
+                    self.string = None
+                }
+                i += run.max(1);
+                continue;
+            }
+            if self.comment > 0 {
+                match (c, next) {
+                    ('*', Some('/')) => {
+                        self.comment -= 1;
+                        i += 1
+                    }
+                    ('/', Some('*')) => {
+                        self.comment += 1;
+                        i += 1
+                    }
+                    _ => (),
+                }
+                i += 1;
+                continue;
+            }
+            match (c, next) {
+                ('/', Some('/')) => break,
+                ('/', Some('*')) => {
+                    self.comment += 1;
+                    i += 1
+                }
+                ('"', _) | ('\'', _) => {
+                    let run = chars[i..].iter().take_while(|&&e| e == c).count();
+                    // `""` is an empty string
+                    if run != 2 {
+                        self.string = Some((c, run))
+                    }
+                    i += run - 1
+                }
+                ('[', _) | ('{', _) if !head => self.brackets += 1,
+                (']', _) | ('}', _) if !head => self.brackets = self.brackets.saturating_sub(1),
+                _ => (),
+            }
+            i += 1
+        }
+        // a single quote closes on its line, or is part of a bare string such as `it's`
+        if let Some((_, 1)) = self.string {
+            self.string = None
+        }
+        self.continued = self.string.is_none() && self.comment == 0 && line.ends_with('\\')
+    }
+}
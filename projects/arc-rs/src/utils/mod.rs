@@ -2,10 +2,18 @@ pub use arc_ast::utils::*;
 
 use crate::{ParserConfig, Value};
 use crate::Result;
+use arc_ast::serde::from_value;
+use serde::de::DeserializeOwned;
+use std::convert::TryFrom;
 
 pub fn parse_arc(text: &str) -> Result<Value> {
     let cfg = ParserConfig::default();
-    Ok(Value::from(cfg.parse(text)?))
+    Value::try_from(cfg.parse(text)?)
+}
+
+/// Parse arc text into any deserializable type, see [`arc_ast::serde::ValueSerializer`] for the layout of enums.
+pub fn from_str<T: DeserializeOwned>(text: &str) -> Result<T> {
+    from_value(parse_arc(text)?)
 }
 
 
@@ -0,0 +1,149 @@
+use super::*;
+use arc_rs::{ErrorCode, RuntimeError};
+
+#[test]
+fn invalid_syntax() {
+    let e = parse_text("a = 1\n{b\nc = 2").unwrap_err();
+    let diagnostic = e.get_diagnostic().unwrap();
+    assert_eq!(diagnostic.code, ErrorCode::InvalidSyntax);
+    assert_eq!(diagnostic.range.as_ref().unwrap().start.0, 3);
+}
+
+#[test]
+fn key_under_integer() {
+    let ast = parse_text("a = 1\na.b = 2").unwrap();
+    let e = Value::try_from(ast).unwrap_err();
+    assert!(matches!(e, RuntimeError::StructureError(_)));
+    let diagnostic = e.get_diagnostic().unwrap();
+    assert_eq!(diagnostic.code, ErrorCode::NotDict);
+    assert_eq!(diagnostic.range.as_ref().unwrap().start, (2, 7));
+}
+
+#[test]
+fn index_under_dict() {
+    let ast = parse_text("a.b = 1\na.0 = 2").unwrap();
+    let e = Value::try_from(ast).unwrap_err();
+    assert_eq!(e.get_diagnostic().unwrap().code, ErrorCode::NotList);
+}
+
+#[test]
+fn recover_every_broken_line() {
+    let parser = ParserConfig::default();
+    let (ast, diagnostics) = parser.parse_recover(include_str!("recover.arc"));
+    let lines: Vec<_> = diagnostics.iter().map(|d| d.range.as_ref().unwrap().start.0).collect();
+    assert_eq!(lines, vec![6, 9]);
+    assert_eq!(include_str!("recover.out.arc"), format!("{:#?}", Value::try_from(ast).unwrap()))
+}
+
+#[test]
+fn recover_valid_input() {
+    let parser = ParserConfig::default();
+    let (ast, diagnostics) = parser.parse_recover("a = 1\n[b]\n> 2");
+    assert!(diagnostics.is_empty());
+    assert_eq!(format!("{:?}", Value::try_from(ast).unwrap()), format!("{:?}", Value::try_from(parse_text("a = 1\n[b]\n> 2").unwrap()).unwrap()))
+}
+
+#[test]
+fn invalid_byte() {
+    let e = parse_text("mode = 0o789").unwrap_err();
+    assert_eq!(e.get_diagnostic().unwrap().code, ErrorCode::InvalidNumber);
+    let e = parse_text("mode = 0x").unwrap_err();
+    assert_eq!(e.get_diagnostic().unwrap().code, ErrorCode::InvalidNumber);
+}
+
+#[test]
+fn suffix_out_of_range() {
+    let e = parse_text("port = 70000u16").unwrap_err();
+    let diagnostic = e.get_diagnostic().unwrap();
+    assert_eq!(diagnostic.code, ErrorCode::InvalidNumber);
+    assert_eq!(diagnostic.range.as_ref().unwrap().start, (1, 8));
+    assert!(parse_text("a = -129i8").is_err());
+    assert!(parse_text("a = 0x100u8").is_err());
+    assert!(parse_text("a = 1.5u8").is_err());
+    let e = parse_text("a = 1.5i32").unwrap_err();
+    assert_eq!(e.get_diagnostic().unwrap().message, "integer suffix on decimal `1.5i32`");
+    assert!(parse_text("a = 1e39f32").is_err());
+    assert!(parse_text("a = 255u8").is_ok());
+}
+
+#[test]
+fn invalid_escape() {
+    let e = parse_text("a = 1\nb = \"x\\qy\"").unwrap_err();
+    let diagnostic = e.get_diagnostic().unwrap();
+    assert_eq!(diagnostic.code, ErrorCode::InvalidEscape);
+    assert_eq!(diagnostic.range.as_ref().unwrap().start, (2, 7));
+    let e = parse_text("a = \"\\uD800\"").unwrap_err();
+    assert_eq!(e.get_diagnostic().unwrap().code, ErrorCode::InvalidEscape);
+}
+
+#[test]
+fn invalid_datetime() {
+    let e = parse_text("a = 1\nb = 2021-02-29").unwrap_err();
+    let diagnostic = e.get_diagnostic().unwrap();
+    assert_eq!(diagnostic.code, ErrorCode::InvalidDateTime);
+    assert_eq!(diagnostic.range.as_ref().unwrap().start, (2, 5));
+    // only a time with a date can have an offset, the grammar does not match it
+    let e = parse_text("a = 07:32:00+01:00").unwrap_err();
+    assert_eq!(e.get_diagnostic().unwrap().code, ErrorCode::InvalidSyntax);
+    let e = parse_text("a = 1979-05-27T07:32:00.1234567891Z").unwrap_err();
+    assert_eq!(e.get_diagnostic().unwrap().code, ErrorCode::InvalidDateTime);
+}
+
+#[test]
+fn fractional_unit() {
+    let e = parse_text("a = 1\nb = 0.5ns").unwrap_err();
+    let diagnostic = e.get_diagnostic().unwrap();
+    assert_eq!(diagnostic.code, ErrorCode::InvalidNumber);
+    assert_eq!(diagnostic.range.as_ref().unwrap().start, (2, 5));
+}
+
+#[test]
+fn invalid_bytes() {
+    let e = parse_text("a = 1\nb = hex\"abc\"").unwrap_err();
+    let diagnostic = e.get_diagnostic().unwrap();
+    assert_eq!(diagnostic.code, ErrorCode::HandlerFailed);
+    assert_eq!(diagnostic.range.as_ref().unwrap().start, (2, 5));
+    let e = parse_text("a = b64\"Zm9v!\"").unwrap_err();
+    assert_eq!(e.get_diagnostic().unwrap().code, ErrorCode::HandlerFailed);
+}
+
+#[test]
+fn append_one_item() {
+    // `> 1 2` used to be accepted by the grammar, each item needs its own `>` now
+    let e = parse_text("[a]\n> 1 2").unwrap_err();
+    assert_eq!(e.get_diagnostic().unwrap().code, ErrorCode::InvalidSyntax);
+    let value = Value::try_from(parse_text("[a]\n> 1\n> 2").unwrap()).unwrap();
+    assert_eq!(value.pointer("a"), Some(&arc_rs::list![1, 2]));
+}
+
+#[test]
+fn huge_index() {
+    let e = Value::try_from(parse_text("a.9999999999999 = 1").unwrap()).unwrap_err();
+    assert_eq!(e.get_diagnostic().unwrap().code, ErrorCode::InvalidKey);
+    let value = Value::try_from(parse_text("a.3 = 1").unwrap()).unwrap();
+    assert_eq!(value.to_string(), "a = [null, null, null, 1]\n");
+}
+
+#[test]
+fn invalid_query() {
+    let e = parse_text("a = 1\nb = ${a..b}").unwrap_err();
+    let diagnostic = e.get_diagnostic().unwrap();
+    assert_eq!(diagnostic.code, ErrorCode::InvalidSyntax);
+    assert_eq!(diagnostic.range.as_ref().unwrap().start, (2, 5));
+    let e = parse_text("b = ${a[?x ==]}").unwrap_err();
+    assert_eq!(e.get_diagnostic().unwrap().code, ErrorCode::InvalidSyntax);
+}
+
+#[test]
+fn recover_multiline_values() {
+    let input = "sql = \"\"\"\n    a = 1\n    {b.}\n    \"\"\"\n{client.}\nlist = [\n    1,\n    {c = 2},\n]\n{other.}\nz = 3\n[items]\n> 4";
+    let (ast, diagnostics) = ParserConfig::default().parse_recover(input);
+    let lines: Vec<_> = diagnostics.iter().map(|d| d.range.as_ref().unwrap().start.0).collect();
+    assert_eq!(lines, vec![5, 10]);
+    let value = Value::try_from(ast).unwrap();
+    assert_eq!(value.get_as::<String>("sql").unwrap(), "a = 1\n{b.}");
+    // the pairs after a broken head are dropped, not moved into another scope
+    assert_eq!(value.pointer("list"), None);
+    assert_eq!(value.pointer("z"), None);
+    assert_eq!(value.pointer("items.0"), Some(&Value::from(4)));
+}
@@ -6,7 +6,7 @@ fn test() {
     println!("{}", Text::from("x"));
     println!("{:?}", Value::from(Text::from("x")));
     println!("{:?}", AST::string(Text::from("x")));
-    println!("{:?}", Value::from(AST::string(Text::from("x"))));
+    println!("{:?}", Value::try_from(AST::string(Text::from("x"))).unwrap());
     println!("{:?}", dict! {"a": "1"});
 }
 
@@ -36,3 +36,44 @@ fn test() {
 //     };
 //     println!("{}", d);
 // }
+
+fn value(text: &str) -> Value {
+    Value::try_from(parse_text(text).unwrap()).unwrap()
+}
+
+macro_rules! round_trip {
+    ($($F:ident => $path:expr), +,) => {
+        $(round_trip![$F, $path];)+
+    };
+    ($function_name:ident, $file_name:expr) => {
+    #[test]
+    fn $function_name() {
+        let data = value(include_str!(concat!("../", $file_name, ".arc")));
+        let text = data.to_string();
+        assert_eq!(data, value(&text), "{}", text)
+    }
+    };
+}
+
+round_trip![
+    basic => "easy_structure/basic",
+    bytes => "easy_structure/bytes",
+    datetime => "easy_structure/datetime",
+    escape => "easy_structure/escape",
+    float => "easy_structure/float",
+    multiline => "easy_structure/multiline",
+    scope => "easy_structure/scope",
+    unit => "easy_structure/unit",
+    handler => "handler/handler",
+    list_scope => "hard_structure/list_scope",
+    scope_inherit => "hard_structure/scope_inherit",
+    nested_key => "hard_structure/nested_key",
+    doki => "real_structure/doki",
+];
+
+#[test]
+fn emit() {
+    let data = value(include_str!("config.arc"));
+    assert_eq!(include_str!("config.out.arc"), data.to_string());
+    assert_eq!(data, value(&data.to_string()));
+}
@@ -0,0 +1,64 @@
+use super::*;
+use arc_ast::{
+    function::HandlerRegistry,
+    utils::{BigInt, IndexMap},
+};
+use arc_rs::{ErrorCode, RuntimeError};
+use std::net::IpAddr;
+
+fn ip(args: &[Value], _: &IndexMap<String, Value>) -> Result<Value> {
+    let text = match &args[0] {
+        Value::String(s) => s.as_str(),
+        _ => "",
+    };
+    match text.parse::<IpAddr>() {
+        Ok(o) => Ok(Value::from(o.to_string())),
+        Err(e) => Err(RuntimeError::IOError(e.to_string())),
+    }
+}
+
+fn parser() -> ParserConfig {
+    let mut handlers = HandlerRegistry::default();
+    handlers.insert("ip", ip);
+    handlers.insert("sum", |args: &[Value], _: &IndexMap<String, Value>| {
+        let mut sum = BigInt::from(0);
+        for item in args[0].as_vec() {
+            if let Value::Integer(n) = item {
+                sum += n.get_value()
+            }
+        }
+        Ok(Value::from(sum))
+    });
+    handlers.insert("k", |args: &[Value], _: &IndexMap<String, Value>| match &args[0] {
+        Value::Integer(n) => Ok(Value::from(n.get_value() * 1000)),
+        _ => Ok(args[0].clone()),
+    });
+    // units are built in
+    handlers.insert("KB", |_: &[Value], _: &IndexMap<String, Value>| Ok(Value::Null));
+    ParserConfig { handlers, ..ParserConfig::default() }
+}
+
+#[test]
+fn registered_handler() {
+    let ast = parser().parse(include_str!("handler.arc")).unwrap();
+    assert_eq!(include_str!("handler.out.arc"), format!("{:#?}", Value::try_from(ast).unwrap()))
+}
+
+#[test]
+fn rejected_literal() {
+    let e = parser().parse("host = ip\"192.168.1\"").unwrap_err();
+    let diagnostic = e.get_diagnostic().unwrap();
+    assert_eq!(diagnostic.code, ErrorCode::HandlerFailed);
+    assert_eq!(diagnostic.range.as_ref().unwrap().start, (1, 8));
+}
+
+#[test]
+fn replace_builtin_handler() {
+    let mut handlers = HandlerRegistry::default();
+    assert!(handlers.get("b64").is_some());
+    handlers.insert("hex", |args: &[Value], _: &IndexMap<String, Value>| Ok(args[0].clone()));
+    let parser = ParserConfig { handlers, ..ParserConfig::default() };
+    let value = Value::try_from(parser.parse("a = hex\"xyz\"\nb = b64\"Zm9v\"").unwrap()).unwrap();
+    assert_eq!(value.pointer("a"), Some(&Value::from("xyz")));
+    assert!(matches!(value.pointer("b"), Some(Value::Bytes(_))));
+}
@@ -1,4 +1,6 @@
 use super::*;
+use arc_ast::traits::ExtendFormat;
+use arc_rs::{value::Layer, ErrorCode};
 
 macro_rules! run_test {
     ($($F:ident), +,) => {
@@ -8,9 +10,56 @@ macro_rules! run_test {
     #[test]
     fn $function_name() {
         let ast = parse_text(include_str!(concat!($file_name, ".arc"))).unwrap();
-        assert_eq!(include_str!(concat!($file_name, ".out.arc")), format!("{:#?}", Value::from(ast)))
+        assert_eq!(include_str!(concat!($file_name, ".out.arc")), format!("{:#?}", Value::try_from(ast).unwrap()))
     }
     };
 }
 
-run_test![nested_key, negative_key, scope_inherit, extend_others,];
+run_test![nested_key, negative_key, sparse_key, scope_inherit, list_scope, query_cite,];
+
+#[test]
+fn extend_others() {
+    let ast = parse("tests/hard_structure/extend_others.arc").unwrap();
+    assert_eq!(include_str!("extend_others.out.arc"), format!("{:#?}", Value::try_from(ast).unwrap()))
+}
+
+#[test]
+fn import_others() {
+    let ast = parse("tests/hard_structure/import_others.arc").unwrap();
+    assert_eq!(include_str!("import_others.out.arc"), format!("{:#?}", Value::try_from(ast).unwrap()))
+}
+
+#[test]
+fn merge_others() {
+    let ast = parse("tests/hard_structure/merge_others.arc").unwrap();
+    assert_eq!(include_str!("merge_others.out.arc"), format!("{:#?}", Value::try_from(ast).unwrap()))
+}
+
+#[test]
+fn explain_others() {
+    let file = "tests/hard_structure/merge_others.arc";
+    let parser = ParserConfig { file_path: Some(file.into()), ..ParserConfig::default() };
+    let (_, provenance) = parser.parse_explained(&read_to_string(file).unwrap()).unwrap();
+    let chain: Vec<String> = provenance.explain("servers.1.port").iter().map(|d| d.to_string()).collect();
+    assert_eq!(chain, vec![
+        "tests/hard_structure/layers/base.arc:9:10 (extend tests/hard_structure/layers/base.arc)",
+        "tests/hard_structure/layers/prod.arc (extend tests/hard_structure/layers/prod.arc)",
+    ]);
+    // deleted by `debug = null` in prod
+    assert_eq!(provenance.explain("debug").len(), 2);
+    assert_eq!(provenance.get("servers.2.name").unwrap().layer, Layer::Extend("tests/hard_structure/layers/prod.arc".into()));
+    assert_eq!(provenance.get("site.title").unwrap().to_string(), "tests/hard_structure/merge_others.arc:9:9 (document)");
+    // kept, so only base defines it
+    let chain: Vec<String> = provenance.explain("source").iter().map(|d| d.to_string()).collect();
+    assert_eq!(chain, vec!["tests/hard_structure/layers/base.arc:2:10 (extend tests/hard_structure/layers/base.arc)"]);
+}
+
+#[test]
+fn circular_extend() {
+    let e = parse("tests/hard_structure/layers/cycle_a.arc").unwrap_err();
+    let diagnostic = e.get_diagnostic().unwrap();
+    assert_eq!(diagnostic.code, ErrorCode::CircularExtend);
+    let chain: Vec<&str> = diagnostic.message.split(" -> ").collect();
+    assert_eq!(chain.len(), 3);
+    assert!(chain[0].ends_with("cycle_a.arc") && chain[1].ends_with("cycle_b.arc") && chain[2].ends_with("cycle_a.arc"));
+}
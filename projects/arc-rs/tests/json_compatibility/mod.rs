@@ -8,7 +8,7 @@ macro_rules! run_test {
     #[test]
     fn $function_name() {
         let ast = parse_text(include_str!(concat!($file_name, ".json"))).unwrap();
-        println!("{:#?}", Value::from(ast))
+        println!("{:#?}", Value::try_from(ast).unwrap())
     }
     };
 }
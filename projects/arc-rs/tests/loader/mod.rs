@@ -0,0 +1,107 @@
+use arc_rs::{
+    dict, list,
+    value::{Layer, MergePolicy, MergeStrategy},
+    Loader,
+};
+
+fn layers() -> Loader {
+    Loader::new()
+        .defaults(dict! {"name": "app", "threads": 4, "features": list!["log"]})
+        .file("tests/loader/base.arc")
+        .file("tests/loader/prod.json")
+        .optional_file("tests/loader/local.toml")
+}
+
+#[test]
+fn files() {
+    let out = layers().load().unwrap();
+    assert_eq!(out.get_as::<String>("name").unwrap(), "blog");
+    assert_eq!(out.get_as::<i64>("threads").unwrap(), 4);
+    assert_eq!(out.get_as::<String>("database.host").unwrap(), "db.internal");
+    assert_eq!(out.get_as::<i64>("database.port").unwrap(), 5432);
+    assert_eq!(out.get_as::<i64>("database.pool").unwrap(), 16);
+}
+
+#[test]
+fn env() {
+    let vars = vec![
+        ("APP__DATABASE__PORT", "6543"),
+        ("APP__DEBUG", "true"),
+        ("APP__NAME", "my blog"),
+        ("APP__FEATURES", "[log, cache]"),
+        ("APPLE", "1"),
+        ("OTHER__NAME", "other"),
+    ];
+    let out = layers().env_vars("APP", vars).load().unwrap();
+    assert_eq!(out.get_as::<i64>("database.port").unwrap(), 6543);
+    assert!(out.get_as::<bool>("debug").unwrap());
+    assert_eq!(out.get_as::<String>("name").unwrap(), "my blog");
+    assert_eq!(out.get_as::<Vec<String>>("features").unwrap(), vec!["log", "cache"]);
+    assert!(out.pointer("apple").is_none());
+}
+
+#[test]
+fn env_strings() {
+    let vars = vec![("APP__PASSWORD", "$ecret"), ("APP__ZIP", "01234"), ("APP__QUOTE", "\"unclosed"), ("APP__EMPTY", "null")];
+    let out = Loader::new().env_vars("APP", vars).load().unwrap();
+    assert_eq!(out.get_as::<String>("password").unwrap(), "$ecret");
+    assert_eq!(out.get_as::<String>("zip").unwrap(), "01234");
+    assert_eq!(out.get_as::<String>("quote").unwrap(), "\"unclosed");
+    assert_eq!(out.pointer("empty"), Some(&arc_rs::Value::Null));
+}
+
+#[test]
+#[cfg(unix)]
+fn env_not_unicode() {
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+    std::env::set_var("ARC_LOADER_TEST__NAME", "blog");
+    std::env::set_var("ARC_LOADER_TEST__BAD", OsString::from_vec(vec![0x66, 0xff]));
+    std::env::set_var(OsString::from_vec(vec![0x66, 0xff]), "1");
+    let out = Loader::new().env("ARC_LOADER_TEST").load().unwrap();
+    assert_eq!(out.get_as::<String>("name").unwrap(), "blog");
+    assert!(out.pointer("bad").is_none());
+    std::env::remove_var("ARC_LOADER_TEST__BAD");
+    std::env::remove_var(OsString::from_vec(vec![0x66, 0xff]));
+}
+
+#[test]
+fn env_list_index() {
+    let servers = list![dict! {"host": "a", "port": 1}, dict! {"host": "b", "port": 2}];
+    let loader = Loader::new().defaults(dict! {"servers": servers});
+    let out = loader.env_vars("APP", vec![("APP__SERVERS__1__HOST", "c")]).set("/servers/0/host=d").load().unwrap();
+    assert_eq!(out.get_as::<String>("servers.0.host").unwrap(), "d");
+    assert_eq!(out.get_as::<String>("servers.1.host").unwrap(), "c");
+    assert_eq!(out.get_as::<i64>("servers.1.port").unwrap(), 2);
+    let out = Loader::new().env_vars("APP", vec![("APP__SERVERS__0__HOST", "a")]).load().unwrap();
+    assert_eq!(out, Loader::new().set("/servers/0/host=a").load().unwrap());
+}
+
+#[test]
+fn overrides() {
+    let args = vec!["database.port=7000", "name = \"cli\"", "features.1=trace"];
+    let out = layers().env_vars("APP", vec![("APP__DATABASE__PORT", "6543")]).overrides(args).load().unwrap();
+    assert_eq!(out.get_as::<i64>("database.port").unwrap(), 7000);
+    assert_eq!(out.get_as::<String>("name").unwrap(), "cli");
+    assert_eq!(out.get_as::<Vec<String>>("features").unwrap(), vec!["log", "trace"]);
+    assert!(Loader::new().set("database.port").load().is_err());
+    assert!(Loader::new().file("tests/loader/missing.arc").load().is_err());
+}
+
+#[test]
+fn policy() {
+    let mut policy = MergePolicy::default();
+    policy.set_path("features", MergeStrategy::Append).unwrap();
+    let out = layers().policy(policy).env_vars("APP", vec![("APP__FEATURES", "[cache]")]).load().unwrap();
+    assert_eq!(out.get_as::<Vec<String>>("features").unwrap(), vec!["log", "cache"]);
+}
+
+#[test]
+fn explain() {
+    let (_, provenance) = layers().env_vars("APP", vec![("APP__DATABASE__PORT", "6543")]).set("name=cli").load_explained().unwrap();
+    let layers: Vec<String> = provenance.explain("database.port").iter().map(|d| d.layer.to_string()).collect();
+    assert_eq!(layers, vec!["file tests/loader/base.arc", "env APP__DATABASE__PORT"]);
+    assert_eq!(provenance.get("database.host").unwrap().to_string(), "tests/loader/prod.json (file tests/loader/prod.json)");
+    assert_eq!(provenance.get("database.pool").unwrap().file, Some("tests/loader/prod.json".into()));
+    assert_eq!(provenance.explain("name").len(), 3);
+    assert_eq!(provenance.get("threads").unwrap().layer, Layer::Defaults);
+}
@@ -1,18 +1,23 @@
 use arc_ast::{Value, AST};
 use arc_rs::{ParserConfig, Result};
 use std::{
+    convert::TryFrom,
     fs::{read_to_string},
     path::Path,
 };
 
+mod diagnostics;
 mod display;
 mod easy_structure;
+mod handler;
 mod hard_structure;
 mod json_compatibility;
+mod loader;
 mod real_structure;
+mod typed;
 
 fn parse(file: impl AsRef<Path>) -> Result<AST> {
-    let parser = ParserConfig::default();
+    let parser = ParserConfig { file_path: Some(file.as_ref().to_path_buf()), ..ParserConfig::default() };
     let ast = parser.parse(&read_to_string(file.as_ref())?)?;
     Ok(ast)
 }
@@ -8,7 +8,7 @@ macro_rules! run_test {
     #[test]
     fn $function_name() {
         let ast = parse_text(include_str!(concat!($file_name, ".arc"))).unwrap();
-        assert_eq!(include_str!(concat!($file_name, ".out.arc")), format!("{:#?}", Value::from(ast)))
+        assert_eq!(include_str!(concat!($file_name, ".out.arc")), format!("{:#?}", Value::try_from(ast).unwrap()))
     }
     };
 }
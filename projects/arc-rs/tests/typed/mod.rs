@@ -0,0 +1,83 @@
+use super::*;
+use arc_rs::{from_str, to_string, to_string_pretty, to_value};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Duration};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Config {
+    name: String,
+    version: u8,
+    timeout: Duration,
+    big: Value,
+    backup: Option<String>,
+    missing: Option<bool>,
+    ports: BTreeMap<u16, String>,
+    server: Server,
+    shapes: Vec<Shape>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Server {
+    #[serde(flatten)]
+    address: Address,
+    mode: Mode,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Address {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Mode {
+    Debug,
+    Release,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Circle { radius: f64 },
+    Rect(u32, u32),
+    Named(String),
+    Scaled(u32),
+}
+
+#[test]
+fn from_arc() {
+    let config: Config = from_str(include_str!("config.arc")).unwrap();
+    assert_eq!(config.version, 3);
+    assert_eq!(config.timeout, Duration::from_secs(90));
+    assert_eq!(format!("{:?}", config.big), "123456789012345678901234567890123456789012345");
+    assert_eq!(config.backup, None);
+    assert_eq!(config.ports[&443], "https");
+    assert_eq!(config.server.address, Address { host: String::from("localhost"), port: 8080 });
+    assert_eq!(config.server.mode, Mode::Debug);
+    assert_eq!(
+        config.shapes,
+        vec![Shape::Circle { radius: 1.5 }, Shape::Rect(2, 3), Shape::Named(String::from("unit")), Shape::Scaled(2)]
+    );
+}
+
+#[test]
+fn round_trip() {
+    let config: Config = from_str(include_str!("config.arc")).unwrap();
+    let text = to_string(&config).unwrap();
+    assert!(text.contains("timeout = 90s\n"));
+    assert!(text.contains("shapes = [Circle{radius = 1.5}, Rect[2, 3], Named\"unit\", {Scaled = 2}]\n"));
+    assert_eq!(from_str::<Config>(&text).unwrap(), config);
+    assert_eq!(from_str::<Config>(&to_string_pretty(&config).unwrap()).unwrap(), config);
+    let value = to_value(&config).unwrap();
+    assert_eq!(value.pointer("big"), Some(&config.big));
+    assert_eq!(value.pointer("ports.80"), Some(&Value::from("http")));
+}
+
+#[test]
+fn type_error() {
+    let e = from_str::<Server>("host = 1\nport = 1\nmode = \"Debug\"").unwrap_err();
+    assert_eq!(e.to_string(), "E0104: invalid type: integer `1`, expected a string");
+    let e = from_str::<Address>("host = \"a\"\nport = 70000").unwrap_err();
+    assert_eq!(e.to_string(), "E0104: invalid value: integer `70000`, expected u16");
+    let e = from_str::<Server>("host = \"a\"\nport = 1\nmode = 1").unwrap_err();
+    assert_eq!(e.to_string(), "E0104: expected enum `Mode`, found integer");
+}